@@ -0,0 +1,797 @@
+use std::{
+  fs::{ create_dir_all, read_dir, remove_dir, remove_dir_all, rename, File },
+  io::{ copy, BufReader, Cursor, Error as IoError, ErrorKind, Read, Seek, SeekFrom, Write },
+  path::{ Path, PathBuf },
+  sync::mpsc::{ Receiver, SyncSender, sync_channel },
+  thread::scope
+};
+use flate2::read::GzDecoder;
+use tar::{ Archive as TarArchive, EntryType };
+use zip::ZipArchive;
+use crate::{ error::Error, path::{ get_bin_path, get_include_path } };
+
+// Temporary name of archive single top level directory while it content moved up
+static UNPACK_DIR_NAME: &str = ".protoc-prebuilt-unpack";
+
+// Suffix of sibling temporary directory, installation placed to it before moving in place
+static STAGING_SUFFIX: &str = ".unpack";
+
+// Maximum archive entries count, protobuf sources archives contain few thousands files
+static MAX_ENTRIES: usize = 100_000;
+
+// Maximum archive total unpacked size, protobuf sources archives are about 100 MiB
+static MAX_UNPACKED_SIZE: u64 = 1024 * 1024 * 1024;
+
+// Unix file type bits of symbolic link in zip entry mode
+static UNIX_SYMLINK_MODE: u32 = 0o120000;
+static UNIX_FILE_TYPE_MASK: u32 = 0o170000;
+
+// Protobuf compiler archive format
+#[derive(Debug, PartialEq)]
+pub(crate) enum ArchiveFormat {
+  Zip,
+  TarGz,
+  TarXz,
+  // Single file binary without archive
+  Binary
+}
+
+// Detect archive format by file content magic bytes, if magic bytes unknown, by file name
+// extension, files in other formats are single file binaries
+pub(crate) fn detect_format(file_name: &str, header: &[u8]) -> ArchiveFormat {
+  if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+    return ArchiveFormat::Zip
+  }
+  if header.starts_with(&[0x1f, 0x8b]) {
+    return ArchiveFormat::TarGz
+  }
+  if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+    return ArchiveFormat::TarXz
+  }
+
+  let file_name = file_name.to_lowercase();
+  if file_name.ends_with(".zip") {
+    ArchiveFormat::Zip
+  } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+    ArchiveFormat::TarGz
+  } else if file_name.ends_with(".tar.xz") || file_name.ends_with(".txz") {
+    ArchiveFormat::TarXz
+  } else {
+    ArchiveFormat::Binary
+  }
+}
+
+// Move single top level directory content to passed directory, tarballs usually contain
+// directory with archive name, but binary and includes paths are calculated from passed one
+//
+// Single `bin` or `include` directory is installation structure part, so it isn't moved
+fn flatten_single_dir(dir: &Path) -> Result<(), Error<'static>> {
+  let entries: Vec<_> = read_dir(dir)
+    .map_err(Error::Io)?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(Error::Io)?;
+
+  if entries.len() != 1 || !entries[0].path().is_dir() {
+    return Ok(())
+  }
+  if ["bin", "include"].iter().any(|name| entries[0].file_name() == *name) {
+    return Ok(())
+  }
+
+  // Rename top level directory before, because it may contain entry with same name
+  let unpack_dir = dir.join(UNPACK_DIR_NAME);
+  rename(entries[0].path(), &unpack_dir).map_err(Error::Io)?;
+
+  for entry in read_dir(&unpack_dir).map_err(Error::Io)? {
+    let entry = entry.map_err(Error::Io)?;
+    rename(entry.path(), dir.join(entry.file_name())).map_err(Error::Io)?;
+  }
+
+  remove_dir(&unpack_dir).map_err(Error::Io)
+}
+
+// Convert archive entry name to relative path, entry names are split by both separators,
+// because zip archives created on Windows may use backslashes
+//
+// Return error reason for absolute paths, Windows drive or UNC prefixes and parent
+// directory components, return empty path for archive root entry
+pub(crate) fn sanitize_entry_path(name: &str) -> Result<PathBuf, &'static str> {
+  if name.starts_with('/') || name.starts_with('\\') {
+    return Err("absolute path")
+  }
+
+  let mut path = PathBuf::new();
+  for component in name.split(['/', '\\']) {
+    match component {
+      "" | "." => continue,
+      ".." => return Err("path escape target directory"),
+      _ if component.contains(':') => return Err("path contain drive or stream prefix"),
+      _ => path.push(component)
+    }
+  }
+
+  Ok(path)
+}
+
+// Protobuf compiler archive entries, which are unpacked, paths are relative to archive root
+#[derive(Debug, PartialEq)]
+pub(crate) struct Selection {
+  pub(crate) bin: PathBuf,
+  pub(crate) include: PathBuf
+}
+
+impl Selection {
+  // Select binary and includes paths by protoc version and asset operating system, for old
+  // versions with binary and includes in archive root (see `path` module) includes content
+  // is `google` directory
+  pub(crate) fn new(version: &str, os: &str) -> Self {
+    let bin = get_bin_path(version, os, Path::new(""));
+    let include = get_include_path(version, &bin);
+
+    Selection {
+      bin,
+      include: match include.as_os_str().is_empty() {
+        true => PathBuf::from("google"),
+        false => include
+      }
+    }
+  }
+
+  // Check is archive entry selected, entries may be placed in single top level directory
+  fn contains(&self, path: &Path) -> bool {
+    let mut components = path.components();
+    components.next();
+
+    [path, components.as_path()]
+      .iter()
+      .any(|path| *path == self.bin || path.starts_with(&self.include))
+  }
+}
+
+// Maximum count of decompressed chunks waiting to be read
+static MAX_PENDING_CHUNKS: usize = 16;
+
+// Writer with total size limit, which send written chunks to `ChannelReader`, used to stream
+// output of decompressors, which can't be read, to archive reader in another thread
+struct ChannelWriter {
+  sender: SyncSender<Vec<u8>>,
+  size: u64,
+  limit: u64
+}
+
+impl Write for ChannelWriter {
+  fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+    self.size += data.len() as u64;
+    if self.size > self.limit {
+      return Err(IoError::other("unpacked size exceeds limit"))
+    }
+
+    // Reader dropped, if archive reading stopped
+    self.sender.send(data.to_vec()).map_err(|_| IoError::from(ErrorKind::BrokenPipe))?;
+    Ok(data.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
+
+// Reader of chunks sent by `ChannelWriter`, end of data is writer drop
+struct ChannelReader {
+  receiver: Receiver<Vec<u8>>,
+  chunk: Cursor<Vec<u8>>
+}
+
+impl Read for ChannelReader {
+  fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+    while self.chunk.position() as usize == self.chunk.get_ref().len() {
+      match self.receiver.recv() {
+        Ok(chunk) => self.chunk = Cursor::new(chunk),
+        Err(_) => return Ok(0)
+      }
+    }
+
+    self.chunk.read(buffer)
+  }
+}
+
+// Decompress XZ stream in another thread and extract tar archive from it while decompressing,
+// decompressed stream size is limited
+fn extract_tar_xz<R: Read + Send>(
+  file: R, file_name: &str, extractor: &mut Extractor, limit: u64
+) -> Result<(), Error<'static>> {
+  let (sender, receiver) = sync_channel(MAX_PENDING_CHUNKS);
+  let mut writer = ChannelWriter { sender, size: 0, limit };
+  let reader = ChannelReader { receiver, chunk: Cursor::new(Vec::new()) };
+
+  let (decompressed, extracted) = scope(|scope| {
+    let decompressor = scope.spawn(move || {
+      lzma_rs::xz_decompress(&mut BufReader::new(file), &mut writer)
+    });
+
+    let mut archive = TarArchive::new(reader);
+    let extracted = extractor.tar(&mut archive);
+    // Stop decompressor, if archive reading stopped before stream end
+    drop(archive);
+
+    (decompressor.join(), extracted)
+  });
+
+  match decompressed {
+    Ok(Err(lzma_rs::error::Error::IoError(err))) if err.kind() == ErrorKind::BrokenPipe => {
+      extracted
+    },
+    Ok(Err(err)) => Err(unsafe_entry(file_name, &format!("decompression fail: {}", err))),
+    Ok(Ok(_)) => extracted,
+    Err(_) => Err(unsafe_entry(file_name, "decompression fail"))
+  }
+}
+
+// Archive extraction state, check entries paths, types, count and total unpacked size,
+// if selection passed, other entries are skipped
+struct Extractor<'a> {
+  dir: &'a Path,
+  selection: Option<&'a Selection>,
+  entries: usize,
+  size: u64,
+  max_entries: usize,
+  max_size: u64
+}
+
+impl<'a> Extractor<'a> {
+  fn new(dir: &'a Path, selection: Option<&'a Selection>) -> Self {
+    Extractor {
+      dir,
+      selection,
+      entries: 0,
+      size: 0,
+      max_entries: MAX_ENTRIES,
+      max_size: MAX_UNPACKED_SIZE
+    }
+  }
+
+  // Count entry and get it target path, None for archive root and not selected entries
+  fn entry(&mut self, name: &str) -> Result<Option<PathBuf>, Error<'static>> {
+    self.entries += 1;
+    if self.entries > self.max_entries {
+      return Err(unsafe_entry(name, "entries count exceeds limit"))
+    }
+
+    let path = sanitize_entry_path(name).map_err(|reason| unsafe_entry(name, reason))?;
+
+    Ok(
+      Some(path)
+        .filter(|path| path.components().next().is_some())
+        .filter(|path| match self.selection {
+          Some(selection) => selection.contains(path),
+          None => true
+        })
+        .map(|path| self.dir.join(path))
+    )
+  }
+
+  fn directory(&mut self, name: &str) -> Result<(), Error<'static>> {
+    if let Some(path) = self.entry(name)? {
+      create_dir_all(path).map_err(Error::Io)?;
+    }
+    Ok(())
+  }
+
+  // Write file content with total unpacked size check, real content size counted,
+  // because entry headers may contain false sizes
+  fn file<R: Read>(
+    &mut self, name: &str, reader: &mut R, mode: Option<u32>
+  ) -> Result<(), Error<'static>> {
+    let path = match self.entry(name)? {
+      Some(path) => path,
+      None => return Ok(())
+    };
+
+    if let Some(parent) = path.parent() {
+      create_dir_all(parent).map_err(Error::Io)?;
+    }
+
+    let mut file = File::create(&path).map_err(Error::Io)?;
+    let remaining = self.max_size - self.size;
+    let written = copy(&mut reader.take(remaining + 1), &mut file).map_err(Error::Io)?;
+    if written > remaining {
+      return Err(unsafe_entry(name, "unpacked size exceeds limit"))
+    }
+    self.size += written;
+
+    set_mode(&path, mode)
+  }
+
+  fn zip<R: Read + Seek>(&mut self, archive: &mut ZipArchive<R>) -> Result<(), Error<'static>> {
+    for index in 0..archive.len() {
+      let mut entry = archive.by_index(index).map_err(Error::Zip)?;
+      let name = entry.name().to_string();
+      let mode = entry.unix_mode();
+
+      if mode.is_some_and(|mode| mode & UNIX_FILE_TYPE_MASK == UNIX_SYMLINK_MODE) {
+        return Err(unsafe_entry(&name, "symbolic links are not allowed"))
+      }
+
+      match entry.is_dir() {
+        true => self.directory(&name)?,
+        false => self.file(&name, &mut entry, mode)?
+      }
+    }
+
+    Ok(())
+  }
+
+  fn tar<R: Read>(&mut self, archive: &mut TarArchive<R>) -> Result<(), Error<'static>> {
+    for entry in archive.entries().map_err(Error::Io)? {
+      let mut entry = entry.map_err(Error::Io)?;
+      let name = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+      let mode = entry.header().mode().ok();
+
+      match entry.header().entry_type() {
+        EntryType::Directory => self.directory(&name)?,
+        EntryType::Regular | EntryType::Continuous => self.file(&name, &mut entry, mode)?,
+        // Global extended headers don't contain files
+        EntryType::XGlobalHeader => {},
+        EntryType::Symlink | EntryType::Link => {
+          return Err(unsafe_entry(&name, "symbolic and hard links are not allowed"))
+        },
+        _ => return Err(unsafe_entry(&name, "special files are not allowed"))
+      }
+    }
+
+    Ok(())
+  }
+}
+
+// Create unsafe archive entry error
+fn unsafe_entry(name: &str, reason: &str) -> Error<'static> {
+  Error::UnsafeArchive((name.to_string(), reason.to_string()))
+}
+
+// Set unpacked file permissions from archive entry mode, special bits (setuid, setgid
+// and sticky) are dropped
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: Option<u32>) -> Result<(), Error<'static>> {
+  use std::{ fs::set_permissions, os::unix::fs::PermissionsExt };
+
+  match mode {
+    Some(mode) => set_permissions(path, PermissionsExt::from_mode(mode & 0o777)).map_err(Error::Io),
+    None => Ok(())
+  }
+}
+
+// On non unix platforms files don't have permissions modes
+#[cfg(not(unix))]
+fn set_mode(_: &Path, _: Option<u32>) -> Result<(), Error<'static>> {
+  Ok(())
+}
+
+// Set executable permission to binary file
+#[cfg(unix)]
+pub(crate) fn set_executable(path: &Path) -> Result<(), Error<'static>> {
+  use std::{ fs::set_permissions, os::unix::fs::PermissionsExt };
+  set_permissions(path, PermissionsExt::from_mode(0o755)).map_err(Error::Io)
+}
+
+// On non unix platforms files don't have executable permission
+#[cfg(not(unix))]
+pub(crate) fn set_executable(_: &Path) -> Result<(), Error<'static>> {
+  Ok(())
+}
+
+// Check unpacked binary file have executable permission and set it, if archive don't contain
+// permissions (for example, zip archive created on Windows)
+#[cfg(unix)]
+fn ensure_executable(path: &Path) -> Result<(), Error<'static>> {
+  use std::os::unix::fs::PermissionsExt;
+
+  match path.metadata() {
+    Ok(metadata) if metadata.permissions().mode() & 0o111 == 0 => set_executable(path),
+    _ => Ok(())
+  }
+}
+
+// On non unix platforms files don't have executable permission
+#[cfg(not(unix))]
+fn ensure_executable(_: &Path) -> Result<(), Error<'static>> {
+  Ok(())
+}
+
+// Extract archive file content to passed directory, see `unpack`
+fn extract<R: Read + Seek + Send>(
+  mut file: R, file_name: &str, protoc_out_dir: &Path, selection: Option<&Selection>
+) -> Result<(), Error<'static>> {
+  let mut header = Vec::new();
+  Read::take(&mut file, 8).read_to_end(&mut header).map_err(Error::Io)?;
+  file.seek(SeekFrom::Start(0)).map_err(Error::Io)?;
+
+  match detect_format(file_name, &header) {
+    ArchiveFormat::Zip => {
+      let mut archive = ZipArchive::new(file).map_err(Error::Zip)?;
+      Extractor::new(protoc_out_dir, selection).zip(&mut archive)?;
+    },
+    ArchiveFormat::TarGz => {
+      let mut archive = TarArchive::new(GzDecoder::new(file));
+      Extractor::new(protoc_out_dir, selection).tar(&mut archive)?;
+    },
+    ArchiveFormat::TarXz => {
+      let mut extractor = Extractor::new(protoc_out_dir, selection);
+      extract_tar_xz(file, file_name, &mut extractor, MAX_UNPACKED_SIZE)?;
+    },
+    ArchiveFormat::Binary => {
+      let selection = selection.ok_or_else(|| {
+        Error::Io(IoError::other(format!("`{}` archive format is not recognized", file_name)))
+      })?;
+      let protoc_bin = protoc_out_dir.join(&selection.bin);
+
+      create_dir_all(protoc_bin.parent().unwrap_or(protoc_out_dir)).map_err(Error::Io)?;
+      // Single file binaries don't contain includes, so `include` directory is empty
+      create_dir_all(protoc_out_dir.join("include")).map_err(Error::Io)?;
+
+      let mut binary = File::create(&protoc_bin).map_err(Error::Io)?;
+      copy(&mut file, &mut binary).map_err(Error::Io)?;
+      set_executable(&protoc_bin)?;
+
+      return Ok(())
+    }
+  }
+
+  flatten_single_dir(protoc_out_dir)?;
+
+  match selection {
+    Some(selection) => ensure_executable(&protoc_out_dir.join(&selection.bin)),
+    None => Ok(())
+  }
+}
+
+// Run installation to sibling temporary directory and move it to passed directory only
+// on success, so existing installation directory is always complete, on fail temporary
+// directory removed
+pub(crate) fn install_staged<'a, T, F>(protoc_out_dir: &Path, install: F) -> Result<T, Error<'a>>
+where
+  F: FnOnce(&Path) -> Result<T, Error<'a>>
+{
+  let dir_name = protoc_out_dir.file_name().unwrap_or_default().to_string_lossy();
+  let staging_dir = protoc_out_dir.with_file_name(format!("{}{}", dir_name, STAGING_SUFFIX));
+
+  // Remove directory left by interrupted installation
+  if staging_dir.exists() {
+    remove_dir_all(&staging_dir).map_err(Error::Io)?;
+  }
+  create_dir_all(&staging_dir).map_err(Error::Io)?;
+
+  let value = match install(&staging_dir) {
+    Ok(value) => value,
+    Err(err) => {
+      remove_dir_all(&staging_dir).map_err(Error::Io)?;
+      return Err(err)
+    }
+  };
+
+  if protoc_out_dir.exists() {
+    remove_dir_all(protoc_out_dir).map_err(Error::Io)?;
+  }
+  rename(&staging_dir, protoc_out_dir).map_err(Error::Io)?;
+
+  Ok(value)
+}
+
+// Unpack archive file to passed directory with same as pre-built binaries assets structure,
+// format detected by file content and name
+//
+// If selection passed, only binary and includes are unpacked, single file binary copied
+// to selected binary path, if selection not passed (archive can't be a binary), return error
+//
+// Archives entries which escape target directory, links, special files and archives
+// exceeds entries count or unpacked size limits are rejected with `UnsafeArchive` error
+//
+// Archive extracted by `install_staged`, so rejected archive content is never left on disk
+pub(crate) fn unpack<R: Read + Seek + Send>(
+  file: R, file_name: &str, protoc_out_dir: &Path, selection: Option<&Selection>
+) -> Result<(), Error<'static>> {
+  install_staged(protoc_out_dir, |staging_dir| extract(file, file_name, staging_dir, selection))
+}
+
+#[cfg(test)]
+mod test {
+  use std::{
+    fs::{ read, read_to_string, write, File },
+    io::{ Cursor, Write },
+    path::PathBuf
+  };
+  use flate2::{ write::GzEncoder, Compression };
+  use zip::{ write::FileOptions, ZipWriter };
+  use crate::{ error::Error, test_dir::create_test_dir };
+  use super::{
+    ArchiveFormat,
+    Extractor,
+    Selection,
+    detect_format,
+    extract_tar_xz,
+    sanitize_entry_path,
+    unpack
+  };
+
+  // Tar archive with passed files, directories created by paths
+  fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, content) in files {
+      let mut header = tar::Header::new_gnu();
+      header.set_size(content.len() as u64);
+      header.set_mode(0o755);
+      header.set_cksum();
+      builder.append_data(&mut header, path, *content).unwrap();
+    }
+    builder.into_inner().unwrap()
+  }
+
+  // Tar archive with single entry, header name written directly to bypass builder path checks
+  fn raw_tar(name: &str, entry_type: tar::EntryType, content: &[u8]) -> Vec<u8> {
+    let mut header = tar::Header::new_old();
+    header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+    header.set_entry_type(entry_type);
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+
+    let mut builder = tar::Builder::new(Vec::new());
+    builder.append(&header, content).unwrap();
+    builder.into_inner().unwrap()
+  }
+
+  fn assert_unsafe(result: Result<(), Error>, entry_name: &str) {
+    assert!(matches!(
+      result.unwrap_err(), Error::UnsafeArchive((name, _)) if name == entry_name
+    ));
+  }
+
+  #[test]
+  fn entry_path_sanitize() {
+    assert_eq!(sanitize_entry_path("bin/protoc").unwrap(), PathBuf::from("bin").join("protoc"));
+    assert_eq!(
+      sanitize_entry_path("./include\\a.proto").unwrap(), PathBuf::from("include").join("a.proto")
+    );
+    assert_eq!(sanitize_entry_path("./").unwrap(), PathBuf::new());
+    assert!(sanitize_entry_path("../evil").is_err());
+    assert!(sanitize_entry_path("bin/../../evil").is_err());
+    assert!(sanitize_entry_path("..\\evil").is_err());
+    assert!(sanitize_entry_path("/etc/passwd").is_err());
+    assert!(sanitize_entry_path("\\\\server\\share").is_err());
+    assert!(sanitize_entry_path("C:\\evil").is_err());
+  }
+
+  #[test]
+  fn reject_zip_slip() {
+    let dir = create_test_dir("reject_zip_slip");
+    let path = dir.0.join("evil.zip");
+
+    let mut writer = ZipWriter::new(File::create(&path).unwrap());
+    writer.start_file("bin/protoc", FileOptions::default()).unwrap();
+    writer.start_file("../evil", FileOptions::default()).unwrap();
+    writer.write_all(b"evil").unwrap();
+    writer.finish().unwrap();
+
+    let result = unpack(File::open(&path).unwrap(), "evil.zip", &dir.0.join("out"), None);
+    assert_unsafe(result, "../evil");
+    assert!(!dir.0.join("evil").exists());
+
+    // Partially unpacked archive doesn't look like installation
+    assert!(!dir.0.join("out").exists());
+    assert!(!dir.0.join("out.unpack").exists());
+  }
+
+  #[test]
+  fn reject_links() {
+    let dir = create_test_dir("reject_links");
+
+    let path = dir.0.join("symlink.zip");
+    let mut writer = ZipWriter::new(File::create(&path).unwrap());
+    writer.add_symlink("bin/protoc", "/etc/passwd", FileOptions::default()).unwrap();
+    writer.finish().unwrap();
+
+    let result = unpack(File::open(&path).unwrap(), "symlink.zip", &dir.0.join("zip"), None);
+    assert_unsafe(result, "bin/protoc");
+    assert!(!dir.0.join("zip").exists());
+
+    for (entry_type, name) in [
+      (tar::EntryType::Symlink, "symlink.tar.gz"),
+      (tar::EntryType::Link, "hardlink.tar.gz"),
+      (tar::EntryType::Char, "char.tar.gz")
+    ] {
+      let path = dir.0.join(name);
+      let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+      encoder.write_all(&raw_tar("bin/protoc", entry_type, b"")).unwrap();
+      encoder.finish().unwrap();
+
+      let result = unpack(File::open(&path).unwrap(), name, &dir.0.join(name), None);
+      assert_unsafe(result, "bin/protoc");
+    }
+
+    // Tar path escape
+    let path = dir.0.join("slip.tar.gz");
+    let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+    encoder.write_all(&raw_tar("../evil", tar::EntryType::Regular, b"evil")).unwrap();
+    encoder.finish().unwrap();
+
+    let result = unpack(File::open(&path).unwrap(), "slip.tar.gz", &dir.0.join("slip"), None);
+    assert_unsafe(result, "../evil");
+  }
+
+  #[test]
+  fn limits() {
+    let dir = create_test_dir("archive_limits");
+
+    let mut extractor = Extractor::new(&dir.0, None);
+    extractor.max_size = 10;
+    assert!(extractor.file("a", &mut "12345".as_bytes(), None).is_ok());
+    assert!(extractor.file("b", &mut "12345".as_bytes(), None).is_ok());
+    assert_unsafe(extractor.file("c", &mut "1".as_bytes(), None), "c");
+
+    let mut extractor = Extractor::new(&dir.0, None);
+    extractor.max_entries = 2;
+    assert!(extractor.directory("d").is_ok());
+    assert!(extractor.directory("e").is_ok());
+    assert_unsafe(extractor.directory("f"), "f");
+  }
+
+  #[test]
+  fn format_detection() {
+    assert_eq!(detect_format("a", b"PK\x03\x04rest"), ArchiveFormat::Zip);
+    assert_eq!(detect_format("a.zip", &[0x1f, 0x8b, 0x08]), ArchiveFormat::TarGz);
+    assert_eq!(
+      detect_format("a", &[0xfd, b'7', b'z', b'X', b'Z', 0x00, 0x00]), ArchiveFormat::TarXz
+    );
+    assert_eq!(detect_format("a.tar.gz", b""), ArchiveFormat::TarGz);
+    assert_eq!(detect_format("a.TGZ", b""), ArchiveFormat::TarGz);
+    assert_eq!(detect_format("a.tar.xz", b""), ArchiveFormat::TarXz);
+    assert_eq!(detect_format("a.zip", b""), ArchiveFormat::Zip);
+    assert_eq!(detect_format("protoc-3.22.0-linux-x86_64.exe", b"\x7fELF"), ArchiveFormat::Binary);
+  }
+
+  #[test]
+  fn unpack_zip() {
+    let dir = create_test_dir("unpack_zip");
+    let path = dir.0.join("protoc.zip");
+
+    let mut writer = ZipWriter::new(File::create(&path).unwrap());
+    writer.start_file("bin/protoc", FileOptions::default()).unwrap();
+    writer.write_all(b"binary").unwrap();
+    writer.start_file("include/google/protobuf/empty.proto", FileOptions::default()).unwrap();
+    writer.finish().unwrap();
+
+    let out = dir.0.join("out");
+    unpack(File::open(&path).unwrap(), "protoc", &out, None).unwrap();
+    assert_eq!(read(out.join("bin").join("protoc")).unwrap(), b"binary");
+    assert!(out.join("include").join("google").join("protobuf").join("empty.proto").exists());
+  }
+
+  #[test]
+  fn unpack_tar_gz_with_top_level_dir() {
+    let dir = create_test_dir("unpack_tar_gz");
+    let path = dir.0.join("protoc-22.0-linux-x86_64.tar.gz");
+
+    let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+    encoder.write_all(&tar(&[
+      ("protoc-22.0/bin/protoc", b"binary"),
+      ("protoc-22.0/include/google/protobuf/empty.proto", b""),
+      ("protoc-22.0/protoc-22.0/readme.txt", b"readme")
+    ])).unwrap();
+    encoder.finish().unwrap();
+
+    let out = dir.0.join("out");
+    unpack(File::open(&path).unwrap(), "protoc-22.0-linux-x86_64.tar.gz", &out, None).unwrap();
+    assert_eq!(read(out.join("bin").join("protoc")).unwrap(), b"binary");
+    assert!(out.join("include").join("google").join("protobuf").join("empty.proto").exists());
+    assert_eq!(read_to_string(out.join("protoc-22.0").join("readme.txt")).unwrap(), "readme");
+    assert!(!out.join(".protoc-prebuilt-unpack").exists());
+  }
+
+  #[test]
+  fn unpack_tar_xz() {
+    let dir = create_test_dir("unpack_tar_xz");
+    let path = dir.0.join("protoc.tar.xz");
+
+    let content = tar(&[("bin/protoc", b"binary"), ("include/a.proto", b"")]);
+    let mut compressed = Vec::new();
+    lzma_rs::xz_compress(&mut content.as_slice(), &mut compressed).unwrap();
+    write(&path, compressed).unwrap();
+
+    let out = dir.0.join("out");
+    unpack(File::open(&path).unwrap(), "protoc.tar.xz", &out, None).unwrap();
+    assert_eq!(read(out.join("bin").join("protoc")).unwrap(), b"binary");
+    assert!(out.join("include").join("a.proto").exists());
+
+    // Decompressed stream size limited
+    let mut extractor = Extractor::new(&out, None);
+    let result = extract_tar_xz(File::open(&path).unwrap(), "protoc.tar.xz", &mut extractor, 100);
+    assert_unsafe(result, "protoc.tar.xz");
+
+    // Corrupted stream
+    let result = unpack(Cursor::new(b"\xfd7zXZ\x00\x00corrupted".to_vec()), "a.tar.xz", &out, None);
+    assert_unsafe(result, "a.tar.xz");
+  }
+
+  #[test]
+  fn unpack_single_include_dir() {
+    let dir = create_test_dir("unpack_single_include_dir");
+    let path = dir.0.join("include.tar.gz");
+
+    let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+    encoder.write_all(&tar(&[("include/google/protobuf/empty.proto", b"")])).unwrap();
+    encoder.finish().unwrap();
+
+    let out = dir.0.join("out");
+    unpack(File::open(&path).unwrap(), "include.tar.gz", &out, None).unwrap();
+    assert!(out.join("include").join("google").join("protobuf").join("empty.proto").exists());
+  }
+
+  #[test]
+  fn selection() {
+    let selection = Selection::new("22.0", "windows");
+    assert_eq!(selection.bin, PathBuf::from("bin").join("protoc.exe"));
+    assert_eq!(selection.include, PathBuf::from("include"));
+
+    // Binary and includes in archive root
+    let selection = Selection::new("3.0.0-beta-3", "linux");
+    assert_eq!(selection.bin, PathBuf::from("protoc"));
+    assert_eq!(selection.include, PathBuf::from("google"));
+  }
+
+  #[test]
+  fn unpack_selected() {
+    let dir = create_test_dir("unpack_selected");
+    let path = dir.0.join("protoc.zip");
+
+    // Archive created on Windows don't contain permissions
+    let options = FileOptions::default();
+    let mut writer = ZipWriter::new(File::create(&path).unwrap());
+    writer.start_file("protoc-22.0/bin/protoc", options).unwrap();
+    writer.write_all(b"binary").unwrap();
+    writer.start_file("protoc-22.0/bin/other", options).unwrap();
+    writer.start_file("protoc-22.0/include/google/protobuf/empty.proto", options).unwrap();
+    writer.start_file("protoc-22.0/readme.txt", options).unwrap();
+    writer.add_directory("protoc-22.0/docs", options).unwrap();
+    writer.finish().unwrap();
+
+    let out = dir.0.join("out");
+    let selection = Selection::new("22.0", "linux");
+    unpack(File::open(&path).unwrap(), "protoc.zip", &out, Some(&selection)).unwrap();
+    assert_eq!(read(out.join("bin").join("protoc")).unwrap(), b"binary");
+    assert!(out.join("include").join("google").join("protobuf").join("empty.proto").exists());
+    assert!(!out.join("bin").join("other").exists());
+    assert!(!out.join("readme.txt").exists());
+    assert!(!out.join("docs").exists());
+
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let mode = out.join("bin").join("protoc").metadata().unwrap().permissions().mode();
+      assert_eq!(mode & 0o111, 0o111);
+    }
+  }
+
+  #[test]
+  fn unpack_binary() {
+    let dir = create_test_dir("unpack_binary");
+    let path = dir.0.join("protoc-22.0-linux-x86_64");
+    write(&path, b"\x7fELFbinary").unwrap();
+
+    let out = dir.0.join("out");
+    let protoc_bin = out.join("bin").join("protoc");
+    let selection = Selection::new("22.0", "linux");
+    unpack(File::open(&path).unwrap(), "protoc-22.0-linux-x86_64", &out, Some(&selection))
+      .unwrap();
+    assert_eq!(read(&protoc_bin).unwrap(), b"\x7fELFbinary");
+    assert!(out.join("include").is_dir());
+
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      assert_eq!(protoc_bin.metadata().unwrap().permissions().mode() & 0o777, 0o755);
+    }
+
+    // Binary can't be unpacked without binary path
+    let result = unpack(File::open(&path).unwrap(), "protoc", &dir.0.join("other"), None);
+    assert!(result.is_err());
+  }
+}
@@ -0,0 +1,340 @@
+use std::{
+  env::var,
+  fs::read_to_string,
+  path::{ Path, PathBuf },
+  process::Command
+};
+use crate::{ error::Error, helpers::var_bool };
+
+// Single `.netrc` file entry, entry without machine name is `default` entry
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NetrcEntry {
+  pub(crate) machine: Option<String>,
+  pub(crate) login: String,
+  pub(crate) password: String
+}
+
+// Parse `.netrc` file content, `macdef` definitions skipped
+pub(crate) fn parse_netrc(content: &str) -> Vec<NetrcEntry> {
+  let mut entries = Vec::new();
+  let mut current: Option<NetrcEntry> = None;
+
+  let mut lines = content.lines();
+  let mut tokens: Vec<&str> = Vec::new();
+
+  // Collect tokens line by line, because `macdef` body ends with empty line
+  while let Some(line) = lines.next() {
+    for token in line.split_whitespace() {
+      if token == "macdef" {
+        // Skip macro name and body
+        for body_line in lines.by_ref() {
+          if body_line.trim().is_empty() {
+            break
+          }
+        }
+        break
+      }
+
+      tokens.push(token);
+    }
+  }
+
+  let mut tokens = tokens.into_iter();
+
+  while let Some(token) = tokens.next() {
+    match token {
+      "machine" | "default" => {
+        if let Some(entry) = current.take() {
+          entries.push(entry);
+        }
+
+        let machine = match token {
+          "machine" => tokens.next().map(|machine| machine.to_lowercase()),
+          _ => None
+        };
+
+        current = Some(NetrcEntry { machine, login: String::new(), password: String::new() });
+      },
+      "login" => {
+        if let (Some(entry), Some(login)) = (current.as_mut(), tokens.next()) {
+          entry.login = login.to_string();
+        }
+      },
+      "password" => {
+        if let (Some(entry), Some(password)) = (current.as_mut(), tokens.next()) {
+          entry.password = password.to_string();
+        }
+      },
+      "account" => {
+        tokens.next();
+      },
+      _ => {}
+    }
+  }
+
+  if let Some(entry) = current.take() {
+    entries.push(entry);
+  }
+
+  entries
+}
+
+// Find `.netrc` entry with machine name exactly matched host, `default` entry isn't used,
+// so credentials aren't sent to unknown hosts
+pub(crate) fn find_netrc_machine<'a>(
+  entries: &'a [NetrcEntry], host: &str
+) -> Option<&'a NetrcEntry> {
+  let host = host.to_lowercase();
+
+  entries
+    .iter()
+    .find(|entry| entry.machine.as_deref() == Some(host.as_str()))
+    .filter(|entry| !entry.login.is_empty() || !entry.password.is_empty())
+}
+
+// Get `.netrc` file path from `NETRC` environment variable or home directory,
+// on Windows file named `_netrc`
+fn get_netrc_path() -> Option<PathBuf> {
+  if let Ok(path) = var("NETRC") {
+    return Some(PathBuf::from(path))
+  }
+
+  let (home_key, file_name) = match cfg!(windows) {
+    true => ("USERPROFILE", "_netrc"),
+    false => ("HOME", ".netrc")
+  };
+
+  var(home_key).ok().map(|home| Path::new(&home).join(file_name))
+}
+
+// Read and parse `.netrc` file, non exists or unreadable file means no entries
+pub(crate) fn read_netrc() -> Vec<NetrcEntry> {
+  if var_bool("PROTOC_PREBUILT_NOT_USE_NETRC") {
+    return Vec::new()
+  }
+
+  get_netrc_path()
+    .and_then(|path| read_to_string(path).ok())
+    .map_or_else(Vec::new, |content| parse_netrc(&content))
+}
+
+// Read GitHub authorization token from file, for example, mounted Docker or Kubernetes secret
+fn read_token_file(path: &str) -> Result<Option<String>, Error<'static>> {
+  let content = read_to_string(path)
+    .map_err(|err| Error::Credentials(format!("read token file `{}` fail: {}", path, err)))?;
+
+  Ok(Some(content.trim().to_string()).filter(|value| !value.is_empty()))
+}
+
+// Run credential helper command with host as last argument, first line of it output is token
+fn run_credential_helper(command: &str, host: &str) -> Result<Option<String>, Error<'static>> {
+  let mut parts = command.split_whitespace();
+  let program = match parts.next() {
+    Some(program) => program,
+    None => return Ok(None)
+  };
+
+  let output = Command::new(program)
+    .args(parts)
+    .arg(host)
+    .output()
+    .map_err(|err| {
+      Error::Credentials(format!("run credential helper `{}` fail: {}", program, err))
+    })?;
+
+  if !output.status.success() {
+    return Err(Error::Credentials(format!(
+      "credential helper `{}` exit with {}", program, output.status
+    )))
+  }
+
+  Ok(
+    String::from_utf8_lossy(&output.stdout)
+      .lines()
+      .next()
+      .map(|line| line.trim().to_string())
+      .filter(|value| !value.is_empty())
+  )
+}
+
+// Get environment variables names which can contain GitHub authorization token for passed host,
+// for GitHub Enterprise Server used same variables as GitHub CLI does
+fn get_github_token_keys(host: &str, custom_key: Option<String>) -> Vec<String> {
+  if let Some(key) = custom_key {
+    return vec![key]
+  }
+
+  match host {
+    "github.com" => vec![String::from("GITHUB_TOKEN")],
+    _ => vec![String::from("GH_ENTERPRISE_TOKEN"), String::from("GITHUB_ENTERPRISE_TOKEN")]
+  }
+}
+
+// Get GitHub authorization token from password of `.netrc` entry for passed host,
+// `default` entry isn't used, because its password is unrelated to GitHub
+fn get_netrc_token(entries: &[NetrcEntry], host: &str) -> Option<String> {
+  find_netrc_machine(entries, host)
+    .map(|entry| entry.password.clone())
+    .filter(|password| !password.is_empty())
+}
+
+// Fetches GitHub authorization token for passed host, sources checked in next order:
+// - environment variable;
+// - file by path from `PROTOC_PREBUILT_GITHUB_TOKEN_FILE` environment variable;
+// - credential helper command from `PROTOC_PREBUILT_CREDENTIAL_HELPER` environment variable;
+// - password of `.netrc` file entry for host.
+pub(crate) fn get_github_token(host: &str) -> Result<Option<String>, Error<'static>> {
+  // Return None if GitHub authorization token usage disable
+  if var_bool("PROTOC_PREBUILT_NOT_ADD_GITHUB_TOKEN") {
+    return Ok(None)
+  }
+
+  // Get contains GitHub authorization token environment variable name
+  let custom_key = var("PROTOC_PREBUILT_GITHUB_TOKEN_ENV_NAME").ok();
+
+  // Fetch, convert to string and discard empty
+  let token = get_github_token_keys(host, custom_key)
+    .iter()
+    .filter_map(|key| var(key).ok())
+    .map(|value| value.trim().to_string())
+    .find(|value| !value.is_empty());
+  if token.is_some() {
+    return Ok(token)
+  }
+
+  if let Ok(path) = var("PROTOC_PREBUILT_GITHUB_TOKEN_FILE") {
+    if let Some(token) = read_token_file(&path)? {
+      return Ok(Some(token))
+    }
+  }
+
+  if let Ok(command) = var("PROTOC_PREBUILT_CREDENTIAL_HELPER") {
+    if let Some(token) = run_credential_helper(&command, host)? {
+      return Ok(Some(token))
+    }
+  }
+
+  Ok(get_netrc_token(&read_netrc(), host))
+}
+
+#[cfg(test)]
+mod test {
+  use std::fs::write;
+  use crate::{ error::Error, test_dir::create_test_dir };
+  use super::{
+    NetrcEntry,
+    find_netrc_machine,
+    get_github_token_keys,
+    get_netrc_token,
+    parse_netrc,
+    read_token_file,
+    run_credential_helper
+  };
+
+  fn entry(machine: Option<&str>, login: &str, password: &str) -> NetrcEntry {
+    NetrcEntry {
+      machine: machine.map(String::from), login: login.to_string(), password: password.to_string()
+    }
+  }
+
+  #[test]
+  fn github_token_keys() {
+    assert_eq!(get_github_token_keys("github.com", None), vec!["GITHUB_TOKEN"]);
+    assert_eq!(
+      get_github_token_keys("ghe.corp.example", None),
+      vec!["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"]
+    );
+    assert_eq!(
+      get_github_token_keys("ghe.corp.example", Some(String::from("CUSTOM_TOKEN"))),
+      vec!["CUSTOM_TOKEN"]
+    );
+  }
+
+  #[test]
+  fn netrc_parse() {
+    let entries = parse_netrc("
+      machine github.com login user password ghp_token
+      machine Mirror.Corp.Example
+        login mirror-user
+        account ignored
+        password mirror-password
+
+      macdef init
+      machine fake.example login fake password fake
+
+      default login anonymous password guest
+    ");
+
+    assert_eq!(entries, vec![
+      entry(Some("github.com"), "user", "ghp_token"),
+      entry(Some("mirror.corp.example"), "mirror-user", "mirror-password"),
+      entry(None, "anonymous", "guest")
+    ]);
+  }
+
+  #[test]
+  fn netrc_find() {
+    let entries = vec![
+      entry(Some("github.com"), "user", "ghp_token"),
+      entry(Some("mirror.corp.example"), "mirror-user", "mirror-password")
+    ];
+
+    assert_eq!(find_netrc_machine(&entries, "github.com").unwrap().password, "ghp_token");
+    assert_eq!(find_netrc_machine(&entries, "MIRROR.corp.example").unwrap().login, "mirror-user");
+    assert!(find_netrc_machine(&entries, "other.example").is_none());
+
+    // Only exactly matched machine
+    let entries = vec![entry(Some("github.com"), "user", "ghp_token"), entry(None, "a", "b")];
+    assert_eq!(find_netrc_machine(&entries, "GitHub.com").unwrap().password, "ghp_token");
+    assert!(find_netrc_machine(&entries, "other.example").is_none());
+  }
+
+  #[test]
+  fn netrc_token() {
+    let entries = vec![entry(Some("github.com"), "user", "ghp_token"), entry(None, "a", "b")];
+    assert_eq!(get_netrc_token(&entries, "github.com").as_deref(), Some("ghp_token"));
+    assert_eq!(get_netrc_token(&entries, "ghe.corp.example"), None);
+
+    // `default` password isn't GitHub token
+    assert_eq!(get_netrc_token(&[entry(None, "a", "b")], "github.com"), None);
+    assert_eq!(get_netrc_token(&[entry(Some("github.com"), "user", "")], "github.com"), None);
+  }
+
+  #[test]
+  fn token_file() {
+    let dir = create_test_dir("token_file");
+    let path = dir.0.join("token");
+
+    write(&path, "ghp_token\n").unwrap();
+    assert_eq!(read_token_file(path.to_str().unwrap()).unwrap().as_deref(), Some("ghp_token"));
+
+    write(&path, " \n").unwrap();
+    assert_eq!(read_token_file(path.to_str().unwrap()).unwrap(), None);
+
+    let result = read_token_file(dir.0.join("non_exists").to_str().unwrap());
+    assert!(matches!(result.unwrap_err(), Error::Credentials { .. }));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn credential_helper() {
+    use std::{ fs::set_permissions, os::unix::fs::PermissionsExt };
+
+    let dir = create_test_dir("credential_helper");
+    let path = dir.0.join("helper.sh");
+    write(&path, "#!/bin/sh\necho \"token-$1-$2\"\necho second line\n").unwrap();
+    set_permissions(&path, PermissionsExt::from_mode(0o755)).unwrap();
+
+    let command = format!("{} arg", path.to_str().unwrap());
+    let result = run_credential_helper(&command, "github.com");
+    assert_eq!(result.unwrap().as_deref(), Some("token-arg-github.com"));
+
+    let result = run_credential_helper("false", "github.com");
+    assert!(matches!(result.unwrap_err(), Error::Credentials { .. }));
+
+    let result = run_credential_helper(
+      dir.0.join("non_exists").to_str().unwrap(), "github.com"
+    );
+    assert!(matches!(result.unwrap_err(), Error::Credentials { .. }));
+  }
+}
@@ -3,24 +3,63 @@ use std::{
   fmt::{ Display, Formatter, Result as FmtResult }
 };
 use zip::result::ZipError;
+use crate::helpers::format_timestamp;
 
 /// Error returned if installation or initialization fail
 #[derive(Debug)]
 pub enum Error<'a> {
   /// Pre-built binary not provided for current platform
   NotProvidedPlatform,
-  /// Required version not exists, contain required version
-  NonExistsVersion(&'a str),
-  /// Pre-built binary not provided for current platform and required version,
-  /// contain required version
-  NonExistsPlatformVersion(&'a str),
+  /// Unsupported platform fallback configuration error, contain error message
+  Fallback(String),
+  /// Explicitly defined platform don't match protoc assets platforms format, contain platform
+  InvalidPlatform(String),
+  /// Version string don't match protobuf repository tags grammar, contain passed version
+  InvalidVersion(&'a str),
+  /// Required version not exists, contain required version and nearest existing versions
+  NonExistsVersion((&'a str, Vec<String>)),
+  /// Pre-built binary not provided for requested platform and required version,
+  /// contain required version and requested platform
+  NonExistsPlatformVersion((&'a str, String)),
+  /// Pre-built binary for requested platform not found in release assets, contain required
+  /// version, requested platform and platforms for which pre-built binaries provided
+  /// in this version
+  NonExistsPlatformAsset((&'a str, String, Vec<String>)),
+  /// Downloaded archive SHA-256 digest don't match published in release, contain archive
+  /// file name, expected and actual digests
+  Checksum((String, String, String)),
+  /// Archive digest verification required, but SHA-256 digest not published in release,
+  /// contain archive file name and published digest in other algorithm, if it exists
+  MissingDigest((String, Option<String>)),
   /// Pre-built binary version check fail, contain tuple with required version
   /// and version returned by binary calling with "--version" argument
   VersionCheck((&'a str, String)),
   /// GitHub API response error, contain response code and body text
   GitHubApi((u16, String)),
+  /// GitHub API rate limit exceeded, contain rate limit reset UNIX timestamp, if it known
+  GitHubRateLimit(Option<u64>),
   /// Force defined paths error, contain error message
   ForcePath(String),
+  /// Proxy URL parse or usage fail, contain proxy URL without password and error message
+  Proxy((String, String)),
+  /// Request URL parse fail, contain URL and error message
+  InvalidUrl((String, String)),
+  /// Request redirects count exceeds limit, contain initial request URL
+  TooManyRedirects(String),
+  /// Additional root certificates bundle load fail, contain bundle path and error message
+  CaBundle((String, String)),
+  /// Credentials source (token file or credential helper) usage fail, contain error message
+  Credentials(String),
+  /// Protobuf compiler build from source fail (`build-from-source` feature), contain error message
+  SourceBuild(String),
+  /// Unknown download source in `PROTOC_PREBUILT_DOWNLOAD_SOURCES`, contain passed source
+  InvalidDownloadSource(String),
+  /// Unknown mirror archive format in `PROTOC_PREBUILT_MIRROR_FORMATS`, contain passed format
+  InvalidArchiveFormat(String),
+  /// Archive can't be safely unpacked, contain offending entry name and reason
+  UnsafeArchive((String, String)),
+  /// Maven repository usage fail, contain error message
+  Maven(String),
   /// Read environment variable fail
   VarError(VarError),
   /// I/O operation error
@@ -37,14 +76,56 @@ impl<'a> Display for Error<'a> {
       Error::NotProvidedPlatform => {
         write!(f, "Pre-built binaries for `{}-{}` platform don't provided", OS, ARCH)
       },
-      Error::NonExistsVersion(version) => {
-        write!(f, "Pre-built binaries version `{}` not exists", version)
+      Error::Fallback(message) => {
+        write!(f, "Unsupported platform fallback error: {}", message)
       },
-      Error::NonExistsPlatformVersion(version) => {
+      Error::InvalidPlatform(platform) => {
         write!(
           f,
-          "Pre-built binaries version `{}` for `{}-{}` platform don't provided",
-          version, OS, ARCH
+          "Invalid platform `{}`, expected protoc asset platform, for example, \
+          \"linux-aarch_64\", \"osx-universal_binary\" or \"win64\"",
+          platform
+        )
+      },
+      Error::InvalidVersion(version) => {
+        write!(f, "Invalid version `{}`, expected protobuf repository tag name without `v` \
+          prefix, for example, \"22.0\" or \"3.21.12\"", version)
+      },
+      Error::NonExistsVersion((version, suggestions)) => {
+        write!(f, "Pre-built binaries version `{}` not exists", version)?;
+        if !suggestions.is_empty() {
+          write!(f, ", did you mean `{}`?", suggestions.join("`, `"))?;
+        }
+        Ok(())
+      },
+      Error::NonExistsPlatformVersion((version, platform)) => {
+        write!(
+          f, "Pre-built binaries version `{}` for `{}` platform don't provided", version, platform
+        )
+      },
+      Error::NonExistsPlatformAsset((version, platform, platforms)) => {
+        write!(
+          f,
+          "Pre-built binaries version `{}` for `{}` platform don't provided, \
+          available platforms: {}",
+          version, platform, platforms.join(", ")
+        )
+      },
+      Error::Checksum((file_name, expected, actual)) => {
+        write!(
+          f,
+          "Pre-built binaries archive `{}` checksum mismatch: expected `{}`, got `{}`",
+          file_name, expected, actual
+        )
+      },
+      Error::MissingDigest((file_name, digest)) => match digest {
+        Some(digest) => write!(
+          f,
+          "Pre-built binaries archive `{}` can't be verified: unsupported digest `{}`",
+          file_name, digest
+        ),
+        None => write!(
+          f, "Pre-built binaries archive `{}` can't be verified: digest not published", file_name
         )
       },
       Error::VersionCheck((required, returned)) => {
@@ -57,9 +138,54 @@ impl<'a> Display for Error<'a> {
       Error::GitHubApi((status, response)) => {
         write!(f, "GitHub API response error: {} {}", status, response)
       },
+      Error::GitHubRateLimit(reset) => {
+        write!(f, "GitHub API rate limit exceeded")?;
+        if let Some(reset) = reset {
+          write!(f, ", limit will be reset at {}", format_timestamp(*reset))?;
+        }
+        write!(
+          f,
+          ", set `GITHUB_TOKEN` environment variable to increase limit \
+          or `PROTOC_PREBUILT_RATE_LIMIT_WAIT` to wait for reset"
+        )
+      },
       Error::ForcePath(message) => {
         write!(f, "Force defined paths error: {}", message)
       },
+      Error::Proxy((proxy, message)) => {
+        write!(f, "Proxy `{}` error: {}", proxy, message)
+      },
+      Error::InvalidUrl((url, message)) => {
+        write!(f, "Invalid request URL `{}`: {}", url, message)
+      },
+      Error::TooManyRedirects(url) => {
+        write!(f, "Too many redirects in request to `{}`", url)
+      },
+      Error::CaBundle((path, message)) => {
+        write!(f, "Root certificates bundle `{}` load error: {}", path, message)
+      },
+      Error::Credentials(message) => {
+        write!(f, "Credentials error: {}", message)
+      },
+      Error::SourceBuild(message) => {
+        write!(f, "Build from source error: {}", message)
+      },
+      Error::InvalidDownloadSource(source) => {
+        write!(f, "Unknown download source `{}`, expected `github` or `maven`", source)
+      },
+      Error::InvalidArchiveFormat(format) => {
+        write!(
+          f,
+          "Unknown archive format `{}`, expected `zip`, `tar.gz`, `tar.xz`, `exe` or `bin`",
+          format
+        )
+      },
+      Error::UnsafeArchive((name, reason)) => {
+        write!(f, "Unsafe archive entry `{}`: {}", name, reason)
+      },
+      Error::Maven(message) => {
+        write!(f, "Maven repository error: {}", message)
+      },
       Error::VarError(err) => write!(f, "{}", err),
       Error::Io(err) => write!(f, "{}", err),
       Error::Ureq(err) => write!(f, "{}", err),
@@ -0,0 +1,259 @@
+use std::{ env::{ split_paths, var, var_os }, ffi::OsStr, path::{ Path, PathBuf } };
+use crate::{ error::Error, version::get_protoc_host_asset_names };
+
+// Default fallback policy for platforms without pre-built binaries
+#[cfg(not(feature = "build-from-source"))]
+static DEFAULT_POLICY: &str = "emulation";
+#[cfg(feature = "build-from-source")]
+static DEFAULT_POLICY: &str = "emulation,source";
+
+// Way to get protobuf compiler for platform without pre-built binaries
+#[derive(Debug, PartialEq)]
+pub(crate) enum FallbackPolicy {
+  // Use pre-built binary for architecture which platform can run by emulation
+  Emulation,
+  // Use `protoc` binary found in `PATH`
+  System,
+  // Use user-provided archive with same as pre-built binaries assets structure
+  Archive,
+  // Build protobuf compiler from source
+  #[cfg(feature = "build-from-source")]
+  Source
+}
+
+// Source of protobuf compiler selected for platform
+#[derive(Debug, PartialEq)]
+pub(crate) enum Source {
+  // Pre-built binaries assets names in preference order, with flag is they are fallback
+  Assets((Vec<String>, bool)),
+  // Path to system `protoc` binary
+  System(PathBuf),
+  // Path to user-provided archive
+  Archive(PathBuf),
+  // Build from protobuf source archive
+  #[cfg(feature = "build-from-source")]
+  Build
+}
+
+// Parse comma separated fallback policies list, empty or "none" value means no fallbacks
+pub(crate) fn parse_fallback_policy(value: &str) -> Result<Vec<FallbackPolicy>, Error<'static>> {
+  value
+    .split(',')
+    .map(|item| item.trim())
+    .filter(|item| !item.is_empty() && *item != "none")
+    .map(|item| match item {
+      "emulation" => Ok(FallbackPolicy::Emulation),
+      "system" => Ok(FallbackPolicy::System),
+      "archive" => Ok(FallbackPolicy::Archive),
+      #[cfg(feature = "build-from-source")]
+      "source" => Ok(FallbackPolicy::Source),
+      #[cfg(not(feature = "build-from-source"))]
+      "source" => Err(Error::Fallback(
+        String::from("fallback policy `source` require `build-from-source` feature")
+      )),
+      _ => Err(Error::Fallback(format!("unknown fallback policy `{}`", item)))
+    })
+    .collect()
+}
+
+// Get fallback policies for platform, platform specific environment variable
+// (`PROTOC_PREBUILT_FALLBACK_$OS_$ARCH`, for example, `PROTOC_PREBUILT_FALLBACK_WINDOWS_AARCH64`)
+// have priority over common `PROTOC_PREBUILT_FALLBACK`
+fn get_fallback_policy(os: &str, arch: &str) -> Result<Vec<FallbackPolicy>, Error<'static>> {
+  let platform_key = format!(
+    "PROTOC_PREBUILT_FALLBACK_{}_{}", os.to_uppercase(), arch.to_uppercase()
+  );
+
+  let value = var(platform_key)
+    .or_else(|_| var("PROTOC_PREBUILT_FALLBACK"))
+    .unwrap_or_else(|_| DEFAULT_POLICY.to_string());
+
+  parse_fallback_policy(&value)
+}
+
+// Get architecture which binaries platform can run by emulation
+fn get_emulation_arch(os: &str, arch: &str) -> Option<&'static str> {
+  match (os, arch) {
+    // Windows 11 on ARM run x86_64 binaries
+    ("windows", "aarch64") => Some("x86_64"),
+    _ => None
+  }
+}
+
+// Find binary file in directories list (`PATH` environment variable value)
+pub(crate) fn find_in_path(name: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+  split_paths(path?)
+    .map(|dir| dir.join(name))
+    .find(|file| file.is_file())
+}
+
+// Inner testable logic of protobuf compiler source selection, system binary finder is passed
+// to replace it in tests
+//
+// Pre-built binaries are used if they provided for platform, else fallback policies
+// are tried in order, if no one is suitable return `NotProvidedPlatform` error
+pub(crate) fn select_source<'a, F>(
+  version: &str,
+  os: &str,
+  arch: &str,
+  host_arch: &str,
+  policies: &[FallbackPolicy],
+  find_system: F,
+  archive: Option<PathBuf>
+) -> Result<Source, Error<'a>>
+where
+  F: Fn() -> Option<PathBuf>
+{
+  if let Ok(names) = get_protoc_host_asset_names(version, os, arch, host_arch) {
+    return Ok(Source::Assets((names, false)))
+  }
+
+  for policy in policies {
+    match policy {
+      FallbackPolicy::Emulation => {
+        let names = get_emulation_arch(os, host_arch)
+          .or_else(|| get_emulation_arch(os, arch))
+          .and_then(|emulation_arch| {
+            get_protoc_host_asset_names(version, os, emulation_arch, emulation_arch).ok()
+          });
+
+        if let Some(names) = names {
+          return Ok(Source::Assets((names, true)))
+        }
+      },
+      FallbackPolicy::System => {
+        if let Some(path) = find_system() {
+          return Ok(Source::System(path))
+        }
+      },
+      FallbackPolicy::Archive => {
+        if let Some(path) = &archive {
+          return Ok(Source::Archive(path.clone()))
+        }
+      },
+      #[cfg(feature = "build-from-source")]
+      FallbackPolicy::Source => return Ok(Source::Build)
+    }
+  }
+
+  Err(Error::NotProvidedPlatform)
+}
+
+// Select protobuf compiler source for platform using fallback policies from environment variables
+pub(crate) fn get_source<'a>(
+  version: &str, os: &str, arch: &str, host_arch: &str
+) -> Result<Source, Error<'a>> {
+  let policies = get_fallback_policy(os, host_arch)?;
+
+  let find_system = || {
+    let name = match os {
+      "windows" => "protoc.exe",
+      _ => "protoc"
+    };
+    find_in_path(name, var_os("PATH").as_deref())
+  };
+
+  let archive = var("PROTOC_PREBUILT_FALLBACK_ARCHIVE")
+    .ok()
+    .filter(|value| !value.trim().is_empty())
+    .map(|value| Path::new(value.trim()).to_path_buf());
+
+  select_source(version, os, arch, host_arch, &policies, find_system, archive)
+}
+
+#[cfg(test)]
+mod test {
+  use std::{ env::join_paths, fs::{ create_dir_all, write }, path::PathBuf };
+  use crate::{ error::Error, test_dir::create_test_dir };
+  use super::{ FallbackPolicy, Source, find_in_path, parse_fallback_policy, select_source };
+
+  #[test]
+  fn parse_policy() {
+    assert_eq!(parse_fallback_policy("emulation").unwrap(), vec![FallbackPolicy::Emulation]);
+    assert_eq!(
+      parse_fallback_policy(" system, archive ").unwrap(),
+      vec![FallbackPolicy::System, FallbackPolicy::Archive]
+    );
+    assert!(parse_fallback_policy("none").unwrap().is_empty());
+    assert!(parse_fallback_policy("").unwrap().is_empty());
+    assert!(matches!(
+      parse_fallback_policy("emulation,build").unwrap_err(), Error::Fallback { .. }
+    ));
+
+    #[cfg(feature = "build-from-source")]
+    assert_eq!(parse_fallback_policy("source").unwrap(), vec![FallbackPolicy::Source]);
+    #[cfg(not(feature = "build-from-source"))]
+    assert!(matches!(parse_fallback_policy("source").unwrap_err(), Error::Fallback { .. }));
+  }
+
+  #[test]
+  fn select_provided_platform() {
+    let result = select_source("22.0", "linux", "x86_64", "x86_64", &[], || None, None);
+    assert_eq!(
+      result.unwrap(), Source::Assets((vec![String::from("protoc-22.0-linux-x86_64")], false))
+    );
+  }
+
+  #[test]
+  fn select_emulation() {
+    let policies = [FallbackPolicy::Emulation];
+
+    let result = select_source("22.0", "windows", "aarch64", "aarch64", &policies, || None, None);
+    assert_eq!(result.unwrap(), Source::Assets((vec![String::from("protoc-22.0-win64")], true)));
+
+    // No emulation for FreeBSD
+    let result = select_source("22.0", "freebsd", "x86_64", "x86_64", &policies, || None, None);
+    assert!(matches!(result.unwrap_err(), Error::NotProvidedPlatform));
+
+    // Emulation disabled
+    let result = select_source("22.0", "windows", "aarch64", "aarch64", &[], || None, None);
+    assert!(matches!(result.unwrap_err(), Error::NotProvidedPlatform));
+  }
+
+  #[test]
+  fn select_system_and_archive() {
+    let policies = [FallbackPolicy::Emulation, FallbackPolicy::System, FallbackPolicy::Archive];
+    let system = || Some(PathBuf::from("/usr/local/bin/protoc"));
+    let archive = Some(PathBuf::from("/opt/protoc.zip"));
+
+    let result = select_source(
+      "22.0", "freebsd", "x86_64", "x86_64", &policies, system, archive.clone()
+    );
+    assert_eq!(result.unwrap(), Source::System(PathBuf::from("/usr/local/bin/protoc")));
+
+    let result = select_source(
+      "22.0", "freebsd", "x86_64", "x86_64", &policies, || None, archive.clone()
+    );
+    assert_eq!(result.unwrap(), Source::Archive(PathBuf::from("/opt/protoc.zip")));
+
+    // Policies order respected
+    let policies = [FallbackPolicy::Archive, FallbackPolicy::System];
+    let result = select_source("22.0", "freebsd", "x86_64", "x86_64", &policies, system, archive);
+    assert_eq!(result.unwrap(), Source::Archive(PathBuf::from("/opt/protoc.zip")));
+  }
+
+  #[cfg(feature = "build-from-source")]
+  #[test]
+  fn select_build() {
+    let policies = [FallbackPolicy::System, FallbackPolicy::Source];
+
+    let result = select_source("22.0", "freebsd", "x86_64", "x86_64", &policies, || None, None);
+    assert_eq!(result.unwrap(), Source::Build);
+
+    // Pre-built binaries preferred
+    let result = select_source("22.0", "linux", "x86_64", "x86_64", &policies, || None, None);
+    assert!(matches!(result.unwrap(), Source::Assets { .. }));
+  }
+
+  #[test]
+  fn find_binary_in_path() {
+    let dir = create_test_dir("find_in_path");
+    create_dir_all(dir.0.join("bin")).unwrap();
+    write(dir.0.join("bin").join("protoc"), "").unwrap();
+
+    let path = join_paths([dir.0.join("empty"), dir.0.join("bin")]).unwrap();
+    assert_eq!(find_in_path("protoc", Some(&path)), Some(dir.0.join("bin").join("protoc")));
+    assert_eq!(find_in_path("protoc-other", Some(&path)), None);
+    assert_eq!(find_in_path("protoc", None), None);
+  }
+}
@@ -0,0 +1,167 @@
+use std::env::var;
+use url::Url;
+use crate::error::Error;
+
+// Default GitHub instance and repository with protobuf releases
+static DEFAULT_URL: &str = "https://github.com";
+static DEFAULT_API_URL: &str = "https://api.github.com";
+static DEFAULT_REPOSITORY: &str = "protocolbuffers/protobuf";
+
+// GitHub instance (github.com or GitHub Enterprise Server) and repository settings
+#[derive(Debug, PartialEq)]
+pub(crate) struct GitHub {
+  pub(crate) url: String,
+  pub(crate) api_url: String,
+  pub(crate) repository: String
+}
+
+// Parse URL and remove trailing slash
+fn prepare_url(url: &str) -> Result<String, Error<'static>> {
+  let url = url.trim().trim_end_matches('/');
+  Url::parse(url).map_err(|err| Error::InvalidUrl((url.to_string(), err.to_string())))?;
+  Ok(url.to_string())
+}
+
+impl GitHub {
+  // Inner testable logic of settings creation, for GitHub Enterprise Server
+  // API URL by default is `$URL/api/v3`
+  pub(crate) fn new(
+    url: Option<String>, api_url: Option<String>, repository: Option<String>
+  ) -> Result<Self, Error<'static>> {
+    let url = prepare_url(url.as_deref().unwrap_or(DEFAULT_URL))?;
+
+    let api_url = match api_url {
+      Some(api_url) => prepare_url(&api_url)?,
+      None if url == DEFAULT_URL => DEFAULT_API_URL.to_string(),
+      None => format!("{}/api/v3", url)
+    };
+
+    let repository = repository.map_or(DEFAULT_REPOSITORY.to_string(), |value| {
+      value.trim().trim_matches('/').to_string()
+    });
+    let is_valid_repository = matches!(
+      repository.split_once('/'),
+      Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/')
+    );
+    if !is_valid_repository {
+      return Err(Error::InvalidUrl((
+        repository, String::from("repository should be in `owner/name` form")
+      )))
+    }
+
+    Ok(GitHub { url, api_url, repository })
+  }
+
+  // Read settings from environment variables
+  pub(crate) fn from_env() -> Result<Self, Error<'static>> {
+    let get = |key| var(key).ok().filter(|value| !value.trim().is_empty());
+
+    GitHub::new(
+      get("PROTOC_PREBUILT_GITHUB_URL"),
+      get("PROTOC_PREBUILT_GITHUB_API_URL"),
+      get("PROTOC_PREBUILT_GITHUB_REPOSITORY")
+    )
+  }
+
+  // Host of GitHub instance, used to choose authorization token
+  pub(crate) fn host(&self) -> String {
+    Url::parse(&self.url)
+      .ok()
+      .and_then(|url| url.host_str().map(String::from))
+      .unwrap_or_default()
+  }
+
+  // Origins of GitHub instance web and API URLs, authorization token can be sent only to them
+  pub(crate) fn origins(&self) -> Vec<String> {
+    [&self.url, &self.api_url]
+      .iter()
+      .filter_map(|url| Url::parse(url).ok())
+      .filter(|url| url.scheme() == "https")
+      .map(|url| url.origin().ascii_serialization())
+      .collect()
+  }
+
+  // Origin of GitHub instance API URL, only its responses contain GitHub rate limit headers
+  pub(crate) fn api_origin(&self) -> Option<String> {
+    Url::parse(&self.api_url).ok().map(|url| url.origin().ascii_serialization())
+  }
+
+  // API URL of release by version tag
+  pub(crate) fn release_url(&self, version: &str) -> String {
+    format!("{}/repos/{}/releases/tags/v{}", self.api_url, self.repository, version)
+  }
+
+  // API URL of repository tags list page
+  pub(crate) fn tags_url(&self, page: u32) -> String {
+    format!("{}/repos/{}/tags?per_page=100&page={}", self.api_url, self.repository, page)
+  }
+
+  // Download URL of release asset
+  pub(crate) fn asset_url(&self, version: &str, asset_file_name: &str) -> String {
+    format!("{}/{}/releases/download/v{}/{}", self.url, self.repository, version, asset_file_name)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::error::Error;
+  use super::GitHub;
+
+  #[test]
+  fn default_settings() {
+    let github = GitHub::new(None, None, None).unwrap();
+    assert_eq!(github.host(), "github.com");
+    assert_eq!(github.origins(), vec!["https://github.com", "https://api.github.com"]);
+    assert_eq!(github.api_origin().as_deref(), Some("https://api.github.com"));
+    assert_eq!(
+      github.release_url("22.0"),
+      "https://api.github.com/repos/protocolbuffers/protobuf/releases/tags/v22.0"
+    );
+    assert_eq!(
+      github.tags_url(2),
+      "https://api.github.com/repos/protocolbuffers/protobuf/tags?per_page=100&page=2"
+    );
+    assert_eq!(
+      github.asset_url("22.0", "protoc-22.0-win64.zip"),
+      "https://github.com/protocolbuffers/protobuf/releases/download/v22.0/protoc-22.0-win64.zip"
+    );
+  }
+
+  #[test]
+  fn enterprise_settings() {
+    let github = GitHub::new(
+      Some(String::from("https://ghe.corp.example/")), None, Some(String::from("mirrors/protobuf"))
+    ).unwrap();
+    assert_eq!(github.host(), "ghe.corp.example");
+    assert_eq!(github.api_url, "https://ghe.corp.example/api/v3");
+    assert_eq!(github.origins(), vec!["https://ghe.corp.example", "https://ghe.corp.example"]);
+    assert_eq!(
+      github.release_url("22.0"),
+      "https://ghe.corp.example/api/v3/repos/mirrors/protobuf/releases/tags/v22.0"
+    );
+    assert_eq!(
+      github.asset_url("22.0", "a.zip"),
+      "https://ghe.corp.example/mirrors/protobuf/releases/download/v22.0/a.zip"
+    );
+
+    let github = GitHub::new(
+      Some(String::from("http://ghe.corp.example")),
+      Some(String::from("https://api.ghe.corp.example")),
+      None
+    ).unwrap();
+    assert_eq!(github.api_url, "https://api.ghe.corp.example");
+    // Token never sent over plain HTTP
+    assert_eq!(github.origins(), vec!["https://api.ghe.corp.example"]);
+  }
+
+  #[test]
+  fn invalid_settings() {
+    let result = GitHub::new(Some(String::from("ghe.corp.example")), None, None);
+    assert!(matches!(result.unwrap_err(), Error::InvalidUrl { .. }));
+
+    for repository in ["protobuf", "/protobuf", "owner/name/extra"] {
+      let result = GitHub::new(None, None, Some(String::from(repository)));
+      assert!(matches!(result.unwrap_err(), Error::InvalidUrl { .. }));
+    }
+  }
+}
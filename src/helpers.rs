@@ -5,22 +5,26 @@ fn str_to_bool(value: &str) -> bool {
   !matches!(value, "" | "0" | "no" | "off" | "false")
 }
 
-// Fetches GitHub authorization token from environment variable
-pub(crate) fn get_github_token() -> Option<String> {
-  // Return None if GitHub authorization token usage disable
-  if var_bool("PROTOC_PREBUILT_NOT_ADD_GITHUB_TOKEN") {
-    return None
-  }
+// Format UNIX timestamp as UTC date and time in "YYYY-MM-DD hh:mm:ss UTC" view,
+// days to civil date conversion by Howard Hinnant algorithm
+pub(crate) fn format_timestamp(timestamp: u64) -> String {
+  let days = (timestamp / 86400) as i64;
+  let seconds = timestamp % 86400;
 
-  // Get contains GitHub authorization token environment variable name
-  let github_token_key = var("PROTOC_PREBUILT_GITHUB_TOKEN_ENV_NAME")
-    .unwrap_or("GITHUB_TOKEN".to_string());
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
 
-  // Fetch, convert to string and discard empty
-  var(github_token_key)
-    .ok()
-    .map(|value| value.trim().to_string())
-    .filter(|value| !value.is_empty())
+  format!(
+    "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+    year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60
+  )
 }
 
 // Fetches the environment variable key from the current process and convert result to boolean,
@@ -34,7 +38,7 @@ pub(crate) fn var_bool<K: AsRef<OsStr>>(key: K) -> bool {
 
 #[cfg(test)]
 mod test {
-  use super::str_to_bool;
+  use super::{ format_timestamp, str_to_bool };
 
   #[test]
   fn true_values() {
@@ -53,4 +57,12 @@ mod test {
     assert!(!str_to_bool("off"));
     assert!(!str_to_bool("false"));
   }
+
+  #[test]
+  fn format_timestamps() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(format_timestamp(951782400), "2000-02-29 00:00:00 UTC");
+    assert_eq!(format_timestamp(1709726400), "2024-03-06 12:00:00 UTC");
+    assert_eq!(format_timestamp(4102444799), "2099-12-31 23:59:59 UTC");
+  }
 }
\ No newline at end of file
@@ -1,19 +1,53 @@
 use std::{
   env::{ consts::{ ARCH, OS }, var },
   fs::metadata,
-  io::{ Error as IoError, ErrorKind },
+  io::Error as IoError,
   path::PathBuf,
   process::Command,
   str::from_utf8
 };
 use crate::{
+  archive::Selection,
   error::Error,
+  fallback::{ Source, get_source },
   helpers::var_bool,
   force::{ get_force_bin, get_force_include },
-  install::install,
+  install::{ install, install_archive },
+  maven::{ get_maven_asset_name, get_maven_classifier, get_maven_classifiers },
   path::{ get_bin_path, get_include_path },
-  version::{ compare_versions, get_protoc_asset_name }
+  release::get_asset_platform,
+  version::{
+    compare_versions,
+    get_host_arch,
+    get_platform_os,
+    get_protoc_host_asset_names,
+    get_protoc_platform_asset_name,
+    validate_platform,
+    validate_version
+  }
 };
+#[cfg(feature = "build-from-source")]
+use crate::source::build;
+
+/// Installed protobuf compiler information
+#[derive(Clone, Debug, PartialEq)]
+pub struct Installation {
+  /// Path to `protoc` binary
+  pub bin: PathBuf,
+  /// Path to `include` directory
+  pub include: PathBuf,
+  /// Installed pre-built binaries asset name, for example, "protoc-22.0-osx-universal_binary",
+  /// `None` if binary path force defined or pre-built binaries are not used
+  pub asset: Option<String>,
+  /// Installed pre-built binaries platform, for example, "osx-universal_binary", or Maven
+  /// artifact classifier, for example, "windows-x86_64", `None` if binary path force defined
+  /// or pre-built binaries are not used
+  pub platform: Option<String>,
+  /// Installed binary is fallback for current platform, for example, universal or x86_64
+  /// (run by Rosetta) binary on Apple Silicon, system binary, user-provided archive or binary
+  /// built from source
+  pub is_fallback: bool
+}
 
 /// Install pre-built protobuf compiler binary if it hasn't been done before
 /// and return paths to it content
@@ -22,48 +56,187 @@ use crate::{
 /// for example, "21.12" or "22.0-rc3"
 /// (see [protobuf repository tags](https://github.com/protocolbuffers/protobuf/tags)).
 ///
+/// Version not matched protobuf repository tags grammar rejected with `InvalidVersion` error.
+///
 /// Return a tuple contains paths to `protoc` binary and `include` directory.
-pub fn init(version: &str) -> Result<(PathBuf, PathBuf), Error> {
-  let protoc_bin: PathBuf = get_force_bin()?.map_or_else(|| -> Result<PathBuf, Error> {
-    let out_dir = PathBuf::from(var("OUT_DIR").map_err(Error::VarError)?);
+pub fn init(version: &str) -> Result<(PathBuf, PathBuf), Error<'_>> {
+  let installation = init_installation(version)?;
+  Ok((installation.bin, installation.include))
+}
 
-    let protoc_asset_name = get_protoc_asset_name(version, OS, ARCH)?;
-    let protoc_out_dir = out_dir.join(&protoc_asset_name);
+/// Same as [`init`], but return [`Installation`] with installed asset information
+///
+/// Platform can be explicitly defined by `PROTOC_PREBUILT_PLATFORM` environment variable,
+/// see [`init_for_platform`].
+pub fn init_installation(version: &str) -> Result<Installation, Error<'_>> {
+  let platform = var("PROTOC_PREBUILT_PLATFORM").ok().filter(|value| !value.trim().is_empty());
+  init_platform(version, platform.as_deref().map(str::trim))
+}
 
-    // Install if installation directory doesn't exist
-    if !protoc_out_dir.exists() {
-      install(version, &out_dir, &protoc_asset_name, &protoc_out_dir)?;
-    }
+/// Same as [`init_installation`], but install pre-built binary for passed platform instead of
+/// current one, platform should be in protoc assets names format, for example, "linux-aarch_64",
+/// "osx-universal_binary" or "win64"
+///
+/// Binary for another platform can't be run, so test run and version check are skipped for it.
+pub fn init_for_platform<'a>(version: &'a str, platform: &str) -> Result<Installation, Error<'a>> {
+  init_platform(version, Some(platform))
+}
+
+// Get installation directories names of GitHub assets and Maven artifacts in preference order
+// with installed platforms, for Maven artifacts platform is artifact classifier
+fn get_installation_candidates(
+  version: &str, protoc_asset_names: &[String], maven_classifiers: &[String]
+) -> Vec<(String, Option<String>)> {
+  protoc_asset_names
+    .iter()
+    .map(|name| (name.clone(), get_asset_platform(&format!("{}.zip", name)).map(String::from)))
+    .chain(maven_classifiers.iter().map(|classifier| {
+      (get_maven_asset_name(version, classifier), Some(classifier.clone()))
+    }))
+    .collect()
+}
+
+// Install pre-built binary for passed or current platform and check it
+fn init_platform<'a>(version: &'a str, platform: Option<&str>) -> Result<Installation, Error<'a>> {
+  // Version and platform used in requests URLs and file paths, so validate it before any usage
+  validate_version(version)?;
+  if let Some(platform) = platform {
+    validate_platform(platform)?;
+  }
+
+  // Binaries for running host architecture preferred, it may differ from compile-time
+  // architecture, if build script run by emulation
+  let host_arch = get_host_arch(OS, ARCH);
+
+  // Binary path, installed asset name and platform, is it fallback and is it can be run
+  // on current platform
+  let (protoc_bin, asset, asset_platform, is_fallback, is_host_binary) = match get_force_bin()? {
+    Some(protoc_bin) => (protoc_bin, None, None, false, true),
+    None => {
+      let out_dir = PathBuf::from(var("OUT_DIR").map_err(Error::VarError)?);
+
+      // For platforms without pre-built binaries fallback policies are used
+      let source = match platform {
+        Some(platform) => {
+          Source::Assets((vec![get_protoc_platform_asset_name(version, platform)], false))
+        },
+        None => get_source(version, OS, ARCH, host_arch)?
+      };
+
+      match source {
+        // First name is preferred, next are fallbacks
+        Source::Assets((protoc_asset_names, is_fallback_source)) => {
+          // Maven repository may provide native binary for platform, which GitHub don't provide
+          let native_classifier = get_maven_classifier(OS, host_arch);
+          let maven_classifiers = get_maven_classifiers(
+            &protoc_asset_names, native_classifier.filter(|_| platform.is_none())
+          );
+          let native_maven_asset_name = native_classifier
+            .map(|classifier| get_maven_asset_name(version, classifier));
+
+          // Install if no one installation directory exist
+          let installed = get_installation_candidates(
+            version, &protoc_asset_names, &maven_classifiers
+          ).into_iter().find(|(name, _)| out_dir.join(name).exists());
+          let (protoc_asset_name, asset_platform) = match installed {
+            Some(installed) => installed,
+            None => install(version, &out_dir, &protoc_asset_names, &maven_classifiers)?
+          };
 
-    Ok(get_bin_path(version, &protoc_out_dir))
-  }, Ok)?;
+          let is_native_maven = native_maven_asset_name.as_ref() == Some(&protoc_asset_name);
+          let is_fallback = !is_native_maven &&
+            (is_fallback_source || protoc_asset_name != protoc_asset_names[0]);
+          let is_host_binary = platform.is_none() || is_native_maven ||
+            get_protoc_host_asset_names(version, OS, ARCH, host_arch)
+              .is_ok_and(|host_asset_names| host_asset_names.contains(&protoc_asset_name));
+
+          let os = platform.map_or(OS, get_platform_os);
+          let protoc_bin = get_bin_path(version, os, &out_dir.join(&protoc_asset_name));
+          (protoc_bin, Some(protoc_asset_name), asset_platform, is_fallback, is_host_binary)
+        },
+        Source::System(protoc_bin) => (protoc_bin, None, None, true, true),
+        Source::Archive(path) => {
+          let protoc_out_dir = out_dir.join(format!("protoc-{}-archive", version));
+          let protoc_bin = get_bin_path(version, OS, &protoc_out_dir);
+
+          // Unpack if installation directory doesn't exist
+          if !protoc_out_dir.exists() {
+            install_archive(&path, &protoc_out_dir, Some(&Selection::new(version, OS)))?;
+          }
+
+          (protoc_bin, None, None, true, true)
+        },
+        #[cfg(feature = "build-from-source")]
+        Source::Build => {
+          let protoc_out_dir = out_dir.join(format!("protoc-{}-source", version));
+          let protoc_bin = get_bin_path(version, OS, &protoc_out_dir);
+
+          // Build if binary doesn't exist, directory may be left by failed build
+          if !protoc_bin.exists() {
+            build(version, &out_dir, &protoc_out_dir)?;
+          }
+
+          (protoc_bin, None, None, true, true)
+        }
+      }
+    }
+  };
 
   // Check binary file exists
   metadata(&protoc_bin).map_err(Error::Io)?;
 
-  // Test run binary file
-  let output = Command::new(&protoc_bin).args(["--version"]).output().map_err(Error::Io)?;
-  if !output.status.success() {
-    return Err(Error::Io(IoError::new(ErrorKind::Other, "test run protoc fail")))
-  }
+  // Test run binary file, binary for another platform may not run
+  let output = match Command::new(&protoc_bin).args(["--version"]).output() {
+    Ok(output) => Some(output),
+    Err(_) if !is_host_binary => None,
+    Err(err) => return Err(Error::Io(err))
+  };
 
-  if !var_bool("PROTOC_PREBUILT_NOT_CHECK_VERSION") {
-    let stdout = match from_utf8(&output.stdout) {
-      Ok(stdout) => stdout,
-      Err(_) => return Err(
-        Error::Io(IoError::new(ErrorKind::Other, "parse test run protoc output fail"))
-      )
-    };
+  if let Some(output) = output {
+    if !output.status.success() {
+      return Err(Error::Io(IoError::other("test run protoc fail")))
+    }
+
+    if !var_bool("PROTOC_PREBUILT_NOT_CHECK_VERSION") {
+      let stdout = match from_utf8(&output.stdout) {
+        Ok(stdout) => stdout,
+        Err(_) => return Err(
+          Error::Io(IoError::other("parse test run protoc output fail"))
+        )
+      };
 
-    let returned = stdout.trim().replace("libprotoc ", "");
+      let returned = stdout.trim().replace("libprotoc ", "");
 
-    if !compare_versions(version, &returned) {
-      return Err(Error::VersionCheck((version, returned)))
+      if !compare_versions(version, &returned) {
+        return Err(Error::VersionCheck((version, returned)))
+      }
     }
   }
-
   let protoc_include: PathBuf = get_force_include()?
     .map_or_else(|| Ok(get_include_path(version, &protoc_bin)), Ok)?;
 
-  Ok((protoc_bin, protoc_include))
+  Ok(Installation {
+    bin: protoc_bin, include: protoc_include, asset, platform: asset_platform, is_fallback
+  })
+}
+#[cfg(test)]
+mod test {
+  use super::get_installation_candidates;
+
+  #[test]
+  fn installation_candidates() {
+    let names = vec![String::from("protoc-22.0-win64")];
+    let classifiers = vec![String::from("windows-aarch_64"), String::from("windows-x86_64")];
+
+    assert_eq!(
+      get_installation_candidates("22.0", &names, &classifiers),
+      vec![
+        (String::from("protoc-22.0-win64"), Some(String::from("win64"))),
+        (
+          String::from("protoc-22.0-maven-windows-aarch_64"), Some(String::from("windows-aarch_64"))
+        ),
+        (String::from("protoc-22.0-maven-windows-x86_64"), Some(String::from("windows-x86_64")))
+      ]
+    );
+  }
 }
\ No newline at end of file
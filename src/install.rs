@@ -1,120 +1,993 @@
-use std::{ fs::{ remove_file, File }, io::copy, path::{ Path, PathBuf } };
+use std::{
+  env::var,
+  fs::{ remove_file, File },
+  io::{ copy, Cursor, Error as IoError, Read, Seek, SeekFrom, Write },
+  path::Path,
+  time::{ Duration, Instant }
+};
+use sha2::{ Digest, Sha256 };
 use ureq::Response;
-use zip::ZipArchive;
-use crate::{ error::Error, helpers::get_github_token, request::request_with_token };
-
-// Check is repository tag exists for passed version
-fn check_version_exists<'a>(version: &'a str, token: &Option<String>) -> Result<(), Error<'a>> {
-  match request_with_token(
-    &format!("https://api.github.com/repos/protocolbuffers/protobuf/releases/tags/v{}", version),
-    token
-  ) {
-    Ok(_) => Ok(()),
-    Err(ureq::Error::Status(code, response)) => {
-      match code {
-        404 => Err(Error::NonExistsVersion(version)),
-        _ => {
-          let text = response.into_string().map_err(Error::Io)?;
-          Err(Error::GitHubApi((code, text)))
-        }
-      }
+use crate::{
+  archive::{ Selection, unpack },
+  credentials::get_github_token,
+  error::Error,
+  github::GitHub,
+  helpers::var_bool,
+  maven::install_maven,
+  progress::{ ProgressReader, Timings, report_finish },
+  release::{ Asset, Release, Tag, get_asset_platform, get_sha256_digest },
+  request::{ Settings, request_with_token },
+  version::{ get_platform_os, suggest_versions }
+};
+
+// Maximum fetched repository tags list pages, protobuf repository have about 400 tags
+static MAX_TAGS_PAGES: u32 = 10;
+
+// Get versions from repository tags names, tags without `v` prefix skipped
+fn get_versions(
+  github: &GitHub, token: &Option<String>, settings: &Settings
+) -> Result<Vec<String>, Error<'static>> {
+  let mut versions = Vec::new();
+
+  for page in 1..=MAX_TAGS_PAGES {
+    let tags: Vec<Tag> = request_with_token(&github.tags_url(page), token, settings)?
+      .into_json()
+      .map_err(Error::Io)?;
+    let is_last = tags.len() < 100;
+
+    versions.extend(
+      tags.into_iter().filter_map(|tag| tag.name.strip_prefix('v').map(String::from))
+    );
+
+    if is_last {
+      break
+    }
+  }
+
+  Ok(versions)
+}
+
+// Get release for passed version, if repository tag exists
+fn get_release<'a>(
+  github: &GitHub, version: &'a str, token: &Option<String>, settings: &Settings
+) -> Result<Release, Error<'a>> {
+  match request_with_token(&github.release_url(version), token, settings) {
+    Ok(response) => response.into_json::<Release>().map_err(Error::Io),
+    Err(Error::Ureq(err)) => match *err {
+      ureq::Error::Status(404, _) => {
+        // Suggestions are optional, so tags list fetch errors ignored
+        let versions = get_versions(github, token, settings).unwrap_or_default();
+        Err(Error::NonExistsVersion((version, suggest_versions(version, &versions))))
+      },
+      ureq::Error::Status(code, response) => {
+        let text = response.into_string().map_err(Error::Io)?;
+        Err(Error::GitHubApi((code, text)))
+      },
+      err => Err(Error::Ureq(Box::new(err)))
     },
-    Err(err) => Err(Error::Ureq(Box::new(err)))
+    Err(err) => Err(err)
+  }
+}
+
+// Get release assets mirror URL, mirror should have same as GitHub releases structure
+// (`$MIRROR/v$VERSION/$ASSET_FILE_NAME`)
+fn get_mirror_url() -> Option<String> {
+  var("PROTOC_PREBUILT_MIRROR_URL")
+    .ok()
+    .map(|value| value.trim().trim_end_matches('/').to_string())
+    .filter(|value| !value.is_empty())
+}
+
+// Get asset file name extension by mirror archive format name, bare binaries may be published
+// with `.exe` extension (as Maven does) or without extension
+fn get_format_extension(format: &str) -> Result<&'static str, Error<'static>> {
+  match format {
+    "zip" => Ok(".zip"),
+    "tar.gz" => Ok(".tar.gz"),
+    "tar.xz" => Ok(".tar.xz"),
+    "exe" => Ok(".exe"),
+    "bin" => Ok(""),
+    _ => Err(Error::InvalidArchiveFormat(format.to_string()))
   }
 }
 
-// Download required version asset
-fn download<'a>(
-  version: &'a str, token: &Option<String>, protoc_asset_file_name: &str
+// Parse comma separated mirror archive formats list to assets files names extensions
+pub(crate) fn parse_mirror_formats(value: &str) -> Result<Vec<&'static str>, Error<'static>> {
+  let extensions = value
+    .split(',')
+    .map(|item| item.trim())
+    .filter(|item| !item.is_empty())
+    .map(get_format_extension)
+    .collect::<Result<Vec<&str>, Error>>()?;
+
+  match extensions.is_empty() {
+    true => Err(Error::InvalidArchiveFormat(value.to_string())),
+    false => Ok(extensions)
+  }
+}
+
+// Get assets files names extensions, which tried on mirror, from
+// `PROTOC_PREBUILT_MIRROR_FORMATS` environment variable, default is zip only
+fn get_mirror_extensions() -> Result<Vec<&'static str>, Error<'static>> {
+  match var("PROTOC_PREBUILT_MIRROR_FORMATS") {
+    Ok(value) => parse_mirror_formats(&value),
+    Err(_) => Ok(vec![".zip"])
+  }
+}
+
+// Format required version asset URL on GitHub or mirror
+fn get_asset_url(
+  github: &GitHub, mirror: &Option<String>, version: &str, protoc_asset_file_name: &str
+) -> String {
+  match mirror {
+    Some(mirror) => format!("{}/v{}/{}", mirror, version, protoc_asset_file_name),
+    None => github.asset_url(version, protoc_asset_file_name)
+  }
+}
+
+// Strip archive or binary extension from asset file name, bare binary name returned as is
+fn strip_asset_extension(file_name: &str) -> &str {
+  [".zip", ".tar.gz", ".tar.xz", ".exe"]
+    .iter()
+    .find_map(|extension| file_name.strip_suffix(extension))
+    .unwrap_or(file_name)
+}
+
+// Get platform of protoc asset file name in any supported format
+fn get_file_platform(file_name: &str) -> Option<String> {
+  get_asset_platform(&format!("{}.zip", strip_asset_extension(file_name))).map(String::from)
+}
+
+// Get requested platform by preferred asset file name for errors, asset file name returned,
+// if it isn't protoc asset name (for example, source archive)
+fn get_requested_platform(protoc_asset_file_names: &[String]) -> String {
+  let name = protoc_asset_file_names.first().map_or("", |name| name.as_str());
+  get_file_platform(name).unwrap_or_else(|| name.to_string())
+}
+
+// Download required version asset for passed platform, platform used in errors only,
+// error statuses of mirrors and Maven repositories aren't GitHub API errors, so returned as is
+pub(crate) fn download<'a>(
+  version: &'a str, platform: &str, url: &str, token: &Option<String>, settings: &Settings
 ) -> Result<Response, Error<'a>> {
-  match request_with_token(
-    &format!(
-      "https://github.com/protocolbuffers/protobuf/releases/download/v{}/{}",
-      version, protoc_asset_file_name
-    ),
-    token
-  ) {
+  match request_with_token(url, token, settings) {
     Ok(response) => Ok(response),
-    Err(ureq::Error::Status(code, response)) => {
-      match code {
-        404 => Err(Error::NonExistsPlatformVersion(version)),
-        _ => {
-          let text = response.into_string().map_err(Error::Io)?;
-          Err(Error::GitHubApi((code, text)))
-        }
-      }
+    Err(Error::Ureq(err)) => match *err {
+      ureq::Error::Status(404, _) => {
+        Err(Error::NonExistsPlatformVersion((version, platform.to_string())))
+      },
+      ureq::Error::Status(code, response) if settings.is_github_url(url) => {
+        let text = response.into_string().map_err(Error::Io)?;
+        Err(Error::GitHubApi((code, text)))
+      },
+      err => Err(Error::Ureq(Box::new(err)))
     },
-    Err(err) => Err(Error::Ureq(Box::new(err)))
+    Err(err) => Err(err)
   }
 }
 
-// Download and unpack requred protobuf compiler version and platform
-pub(crate) fn install<'a>(
-  version: &'a str, out_dir: &Path, protoc_asset_name: &String, protoc_out_dir: &PathBuf
-) -> Result<(), Error<'a>> {
-  let token = get_github_token();
+// Find asset for first found platform of guessed assets file names in release assets listing,
+// guessed names may be in any mirror format, but release contain only zip assets
+fn find_release_asset<'a, 'b>(
+  release: &'b Release, version: &'a str, protoc_asset_file_names: &[String]
+) -> Result<&'b Asset, Error<'a>> {
+  protoc_asset_file_names
+    .iter()
+    .find_map(|name| get_file_platform(name).and_then(|platform| release.find_asset(&platform)))
+    .ok_or_else(|| Error::NonExistsPlatformAsset((
+      version, get_requested_platform(protoc_asset_file_names), release.platforms()
+    )))
+}
 
-  check_version_exists(version, &token)?;
+// Download required version asset by guessed file names (preferred first, next are fallbacks),
+// if no one found, use release assets listing from GitHub API to find asset for same platforms,
+// return response, asset digest, if release metadata was fetched, and downloaded asset
+// file name, which may differ from guessed ones
+//
+// If digest verification required, release metadata fetched before download and assets
+// without published SHA-256 digest rejected
+fn download_asset<'a>(
+  github: &GitHub,
+  mirror: &Option<String>,
+  version: &'a str,
+  protoc_asset_file_names: &[String],
+  token: &Option<String>,
+  verify_digest: bool,
+  settings: &Settings
+) -> Result<(Response, Option<String>, String), Error<'a>> {
+  let platform = get_requested_platform(protoc_asset_file_names);
 
-  let protoc_asset_file_name = format!("{}.zip", protoc_asset_name);
+  if verify_digest {
+    let release = get_release(github, version, token, settings)?;
+    let asset = find_release_asset(&release, version, protoc_asset_file_names)?;
 
-  // Try download binaries
-  let response = download(version, &token, &protoc_asset_file_name)?;
+    // Asset without SHA-256 digest can't be verified, so it not downloaded at all
+    if asset.digest.as_deref().and_then(get_sha256_digest).is_none() {
+      return Err(Error::MissingDigest((asset.name.clone(), asset.digest.clone())))
+    }
+
+    let url = match mirror {
+      Some(_) => get_asset_url(github, mirror, version, &asset.name),
+      None => asset.browser_download_url.clone()
+    };
+
+    let response = download(version, &platform, &url, token, settings)?;
+    return Ok((response, asset.digest.clone(), asset.name.clone()))
+  }
+
+  for protoc_asset_file_name in protoc_asset_file_names {
+    let url = get_asset_url(github, mirror, version, protoc_asset_file_name);
+
+    match download(version, &platform, &url, token, settings) {
+      Err(Error::NonExistsPlatformVersion(_)) => continue,
+      result => return Ok((result?, None, protoc_asset_file_name.clone()))
+    }
+  }
+
+  // GitHub API used only if assets not found, for mirror it can't be used
+  if mirror.is_some() {
+    return Err(Error::NonExistsPlatformVersion((version, platform)))
+  }
+
+  let release = get_release(github, version, token, settings)?;
+  let asset = find_release_asset(&release, version, protoc_asset_file_names)?;
+  let response = download(version, &platform, &asset.browser_download_url, token, settings)?;
+  Ok((response, asset.digest.clone(), asset.name.clone()))
+}
+
+// Calculate hex digest of reader content by passed hash algorithm
+pub(crate) fn calculate_digest<D: Digest + Write, R: Read>(
+  reader: &mut R
+) -> Result<String, Error<'static>> {
+  let mut hasher = D::new();
+  copy(reader, &mut hasher).map_err(Error::Io)?;
+  Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// Reader wrapper, which calculate SHA-256 digest of read content, used to hash asset
+// while downloading, hashing time is measured separately from download time
+struct HashingReader<R> {
+  inner: R,
+  hasher: Sha256,
+  elapsed: Duration
+}
+
+impl<R: Read> HashingReader<R> {
+  fn new(inner: R) -> Self {
+    HashingReader { inner, hasher: Sha256::new(), elapsed: Duration::ZERO }
+  }
 
-  let protoc_asset_file_path = out_dir.join(&protoc_asset_file_name);
+  // Get hex digest of read content and hashing time
+  fn finish(self) -> (String, Duration) {
+    let started = Instant::now();
+    let digest = self.hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+    (digest, self.elapsed + started.elapsed())
+  }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+  fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let size = self.inner.read(buffer)?;
+    let started = Instant::now();
+    self.hasher.update(&buffer[..size]);
+    self.elapsed += started.elapsed();
+    Ok(size)
+  }
+}
+
+// Compare asset digest, if it known, with actual SHA-256 digest, digests of non SHA-256
+// algorithms ignored
+fn check_digest(
+  file_name: &str, digest: &Option<String>, actual: String
+) -> Result<(), Error<'static>> {
+  match digest.as_deref().and_then(get_sha256_digest) {
+    Some(expected) if expected != actual => {
+      Err(Error::Checksum((file_name.to_string(), expected, actual)))
+    },
+    _ => Ok(())
+  }
+}
+
+// Copy not more than `limit` bytes of response content to writer with progress reporting
+// and check it digest, if it known, return download timings without unpack time
+fn copy_asset<W: Write>(
+  response: Response, writer: &mut W, file_name: &str, digest: &Option<String>, limit: u64
+) -> Result<Timings, Error<'static>> {
+  let total = response.header("Content-Length").and_then(|value| value.parse::<u64>().ok());
+  let started = Instant::now();
+  let mut reader = HashingReader::new(
+    ProgressReader::new(response.into_reader(), file_name, total)
+  );
+
+  let size = copy(&mut Read::take(&mut reader, limit), writer).map_err(Error::Io)?;
+  let elapsed = started.elapsed();
+
+  let (actual, hashing) = reader.finish();
+  let started = Instant::now();
+  check_digest(file_name, digest, actual)?;
+
+  Ok(Timings {
+    file_name: file_name.to_string(),
+    size,
+    download: elapsed.saturating_sub(hashing),
+    verify: hashing + started.elapsed(),
+    extract: Duration::ZERO
+  })
+}
 
+// Write response content to asset file and check it digest, if it known, digest calculated
+// while writing, on write fail or digest mismatch asset file removed, asset file name used
+// in errors and progress, because file may be temporary, return file and download timings
+pub(crate) fn write_named_asset(
+  response: Response, path: &Path, file_name: &str, digest: &Option<String>
+) -> Result<(File, Timings), Error<'static>> {
   // Remove previous asset file
-  if protoc_asset_file_path.exists() {
-    remove_file(&protoc_asset_file_path).map_err(Error::Io)?;
+  if path.exists() {
+    remove_file(path).map_err(Error::Io)?;
   }
 
   // Create asset file
   let mut file = File::options()
-    .create(true).read(true).write(true)
-    .open(&protoc_asset_file_path)
+    .create(true).read(true).write(true).truncate(true)
+    .open(path)
     .map_err(Error::Io)?;
 
   // Write content to file
-  let mut response_reader = response.into_reader();
-  copy(&mut response_reader, &mut file).map_err(Error::Io)?;
+  let result = copy_asset(response, &mut file, file_name, digest, u64::MAX)
+    .and_then(|timings| file.seek(SeekFrom::Start(0)).map(|_| timings).map_err(Error::Io));
+
+  match result {
+    Ok(timings) => Ok((file, timings)),
+    Err(err) => {
+      drop(file);
+      // Cleanup error isn't returned to not hide write or digest check error
+      let _ = remove_file(path);
+      Err(err)
+    }
+  }
+}
+
+// Default in-memory downloaded asset size limit
+static DEFAULT_MEMORY_LIMIT: u64 = 256 * 1024 * 1024;
+
+// Parse in-memory downloaded asset size limit in bytes
+fn parse_memory_limit(value: &str) -> Option<u64> {
+  value.trim().parse::<u64>().ok().filter(|limit| *limit > 0)
+}
+
+// Get in-memory downloaded asset size limit, if in-memory mode enabled
+// by `PROTOC_PREBUILT_IN_MEMORY` environment variable
+fn get_memory_limit() -> Option<u64> {
+  if !var_bool("PROTOC_PREBUILT_IN_MEMORY") {
+    return None
+  }
+
+  Some(
+    var("PROTOC_PREBUILT_IN_MEMORY_LIMIT")
+      .ok()
+      .and_then(|value| parse_memory_limit(&value))
+      .unwrap_or(DEFAULT_MEMORY_LIMIT)
+  )
+}
+
+// Read response content to memory buffer with size limit and check it digest, if it known,
+// return buffer and download timings
+fn read_asset(
+  response: Response, file_name: &str, digest: &Option<String>, limit: u64
+) -> Result<(Cursor<Vec<u8>>, Timings), Error<'static>> {
+  let mut buffer = Vec::new();
+
+  let timings = copy_asset(response, &mut buffer, file_name, digest, limit + 1)?;
+  if timings.size > limit {
+    return Err(Error::Io(IoError::other(format!(
+      "`{}` size exceeds in-memory download limit {} bytes", file_name, limit
+    ))))
+  }
+
+  Ok((Cursor::new(buffer), timings))
+}
 
-  // Extract archive and delete file
-  let mut archive = ZipArchive::new(file).map_err(Error::Zip)?;
-  archive.extract(protoc_out_dir).map_err(Error::Zip)?;
+// Unpack downloaded asset, in in-memory mode asset isn't written to disk, else it written to
+// temporary file near installation directory, which removed even if unpack fail, after unpack
+// installation timings reported
+fn unpack_asset(
+  response: Response,
+  file_name: &str,
+  digest: &Option<String>,
+  out_dir: &Path,
+  protoc_out_dir: &Path,
+  selection: Option<&Selection>
+) -> Result<(), Error<'static>> {
+  let (mut timings, started, result) = match get_memory_limit() {
+    Some(limit) => {
+      let (buffer, timings) = read_asset(response, file_name, digest, limit)?;
+      let started = Instant::now();
+      (timings, started, unpack(buffer, file_name, protoc_out_dir, selection))
+    },
+    None => {
+      // Bare binary file name may be same as installation directory name
+      let path = out_dir.join(format!("{}.download", file_name));
+      let (file, timings) = write_named_asset(response, &path, file_name, digest)?;
 
-  remove_file(&protoc_asset_file_path).map_err(Error::Io)?;
+      let started = Instant::now();
+      let result = unpack(file, file_name, protoc_out_dir, selection);
+      // Cleanup error isn't returned to not hide unpack error
+      let _ = remove_file(&path);
+      (timings, started, result)
+    }
+  };
+
+  result?;
+  timings.extract = started.elapsed();
+  report_finish(timings);
 
   Ok(())
 }
 
+// Protobuf compiler download source
+#[derive(Debug, PartialEq)]
+pub(crate) enum DownloadSource {
+  // GitHub releases or releases mirror
+  GitHub,
+  // Maven repository
+  Maven
+}
+
+// Parse comma separated download sources list
+pub(crate) fn parse_download_sources(
+  value: &str
+) -> Result<Vec<DownloadSource>, Error<'static>> {
+  let sources = value
+    .split(',')
+    .map(|item| item.trim())
+    .filter(|item| !item.is_empty())
+    .map(|item| match item {
+      "github" => Ok(DownloadSource::GitHub),
+      "maven" => Ok(DownloadSource::Maven),
+      _ => Err(Error::InvalidDownloadSource(item.to_string()))
+    })
+    .collect::<Result<Vec<DownloadSource>, Error>>()?;
+
+  match sources.is_empty() {
+    true => Err(Error::InvalidDownloadSource(value.to_string())),
+    false => Ok(sources)
+  }
+}
+
+// Get download sources from `PROTOC_PREBUILT_DOWNLOAD_SOURCES` environment variable,
+// default is GitHub only
+fn get_download_sources() -> Result<Vec<DownloadSource>, Error<'static>> {
+  match var("PROTOC_PREBUILT_DOWNLOAD_SOURCES") {
+    Ok(value) => parse_download_sources(&value),
+    Err(_) => Ok(vec![DownloadSource::GitHub])
+  }
+}
+
+// Download and unpack requred protobuf compiler version and platform from GitHub releases
+// (or releases mirror), assets names are tried in passed order, return installed asset name
+// and platform, asset unpacked to directory with same name
+fn install_github<'a>(
+  version: &'a str, out_dir: &Path, protoc_asset_names: &[String], settings: &Settings
+) -> Result<(String, Option<String>), Error<'a>> {
+  let github = GitHub::from_env()?;
+  let settings = settings.clone().with_github(&github);
+  let token = get_github_token(&github.host())?;
+  let mirror = get_mirror_url();
+
+  // Mirror may publish assets in other formats, each asset name tried with all extensions
+  let extensions = match mirror {
+    Some(_) => get_mirror_extensions()?,
+    None => vec![".zip"]
+  };
+  let protoc_asset_file_names: Vec<String> = protoc_asset_names
+    .iter()
+    .flat_map(|name| extensions.iter().map(move |extension| format!("{}{}", name, extension)))
+    .collect();
+
+  let verify_digest = var_bool("PROTOC_PREBUILT_VERIFY_DIGEST");
+
+  // Try download binaries
+  let (response, digest, protoc_asset_file_name) = download_asset(
+    &github, &mirror, version, &protoc_asset_file_names, &token, verify_digest, &settings
+  )?;
+
+  // Downloaded asset may be found in release listing by platform under other name
+  let protoc_asset_name = strip_asset_extension(&protoc_asset_file_name).to_string();
+
+  // Only binary and includes are unpacked
+  let platform = get_file_platform(&protoc_asset_file_name);
+  let selection = Selection::new(version, platform.as_deref().map_or("", get_platform_os));
+
+  unpack_asset(
+    response,
+    &protoc_asset_file_name,
+    &digest,
+    out_dir,
+    &out_dir.join(&protoc_asset_name),
+    Some(&selection)
+  )?;
+
+  Ok((protoc_asset_name, platform))
+}
+
+// Install requred protobuf compiler version from download sources in configured order,
+// GitHub assets names and Maven artifacts classifiers are tried in passed order, return
+// installed asset name and platform (asset platform or Maven artifact classifier), if all
+// sources fail, return first source error
+pub(crate) fn install<'a>(
+  version: &'a str, out_dir: &Path, protoc_asset_names: &[String], maven_classifiers: &[String]
+) -> Result<(String, Option<String>), Error<'a>> {
+  let sources = get_download_sources()?;
+  let settings = Settings::from_env()?;
+  let mut first_err = None;
+
+  for source in sources {
+    let result = match source {
+      DownloadSource::GitHub => install_github(version, out_dir, protoc_asset_names, &settings),
+      DownloadSource::Maven => install_maven(version, out_dir, maven_classifiers, &settings)
+    };
+
+    match result {
+      Ok(installed) => return Ok(installed),
+      Err(err) => {
+        first_err.get_or_insert(err);
+      }
+    }
+  }
+
+  Err(first_err.unwrap_or_else(|| {
+    let platform = protoc_asset_names
+      .first()
+      .and_then(|name| get_asset_platform(&format!("{}.zip", name)).map(String::from))
+      .unwrap_or_default();
+    Error::NonExistsPlatformVersion((version, platform))
+  }))
+}
+
+// Download and unpack protobuf source archive, archives files names are tried in passed order
+#[cfg(feature = "build-from-source")]
+pub(crate) fn install_source<'a>(
+  version: &'a str, out_dir: &Path, source_file_names: &[String], source_dir: &Path
+) -> Result<(), Error<'a>> {
+  let github = GitHub::from_env()?;
+  let settings = Settings::from_env()?.with_github(&github);
+  let token = get_github_token(&github.host())?;
+  let mirror = get_mirror_url();
+
+  // Release assets listing contain only protoc assets platforms, so it not useful here
+  let (response, _, source_file_name) = download_asset(
+    &github, &mirror, version, source_file_names, &token, false, &settings
+  ).map_err(|err| match err {
+    Error::NonExistsPlatformAsset(_) | Error::NonExistsPlatformVersion(_) => {
+      Error::SourceBuild(format!(
+        "protobuf {} source archive not found, tried `{}`",
+        version, source_file_names.join("`, `")
+      ))
+    },
+    err => err
+  })?;
+
+  unpack_asset(response, &source_file_name, &None, out_dir, source_dir, None)?;
+
+  Ok(())
+}
+
+// Unpack user-provided archive with same as pre-built binaries assets structure, if selection
+// passed, only selected entries are unpacked
+pub(crate) fn install_archive(
+  path: &Path, protoc_out_dir: &Path, selection: Option<&Selection>
+) -> Result<(), Error<'static>> {
+  let file = File::open(path).map_err(Error::Io)?;
+  let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+  unpack(file, &file_name, protoc_out_dir, selection)
+}
+
 #[cfg(test)]
 mod test {
-  use crate::error::Error;
-  use super::{ check_version_exists, download };
+  use ureq::Response;
+  use crate::{
+    error::Error,
+    github::GitHub,
+    request::Settings,
+    test_dir::create_test_dir,
+    test_server::{ TestServer, response }
+  };
+  use super::{
+    DownloadSource,
+    HashingReader,
+    download,
+    download_asset,
+    get_asset_url,
+    get_requested_platform,
+    get_release,
+    parse_download_sources,
+    parse_mirror_formats,
+    read_asset,
+    strip_asset_extension,
+    unpack_asset,
+    write_named_asset
+  };
+
+  #[test]
+  fn mirror_formats() {
+    assert_eq!(parse_mirror_formats("zip").unwrap(), vec![".zip"]);
+    assert_eq!(
+      parse_mirror_formats("tar.xz, tar.gz,zip,exe,bin").unwrap(),
+      vec![".tar.xz", ".tar.gz", ".zip", ".exe", ""]
+    );
+    assert!(matches!(
+      parse_mirror_formats("zip,rar").unwrap_err(),
+      Error::InvalidArchiveFormat(format) if format == "rar"
+    ));
+    assert!(matches!(parse_mirror_formats("").unwrap_err(), Error::InvalidArchiveFormat { .. }));
+  }
+
+  #[test]
+  fn download_sources() {
+    assert_eq!(parse_download_sources("github").unwrap(), vec![DownloadSource::GitHub]);
+    assert_eq!(
+      parse_download_sources(" maven , github").unwrap(),
+      vec![DownloadSource::Maven, DownloadSource::GitHub]
+    );
+    assert!(matches!(
+      parse_download_sources("github,npm").unwrap_err(),
+      Error::InvalidDownloadSource(source) if source == "npm"
+    ));
+    assert!(matches!(
+      parse_download_sources(" ").unwrap_err(), Error::InvalidDownloadSource { .. }
+    ));
+  }
+
+  #[test]
+  fn get_release_fail_suggestions() {
+    let tags = r#"[{"name":"v3.21.12"},{"name":"v3.21.11"},{"name":"v22.0"},{"name":"other"}]"#;
+    let server = TestServer::new(vec![
+      response(404, &[], b""),
+      response(200, &[], tags.as_bytes())
+    ]);
+    let github = GitHub::new(Some(server.url("")), Some(server.url("/api")), None).unwrap();
+
+    let result = get_release(&github, "3.21.13", &None, &settings(&github));
+    assert!(matches!(
+      result.unwrap_err(),
+      Error::NonExistsVersion((version, suggestions))
+        if version == "3.21.13" && suggestions == vec!["3.21.12", "3.21.11"]
+    ));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].starts_with(
+      "GET /api/repos/protocolbuffers/protobuf/tags?per_page=100&page=1 "
+    ));
+  }
+
+  // Requests settings for GitHub source
+  fn settings(github: &GitHub) -> Settings {
+    Settings::from_env().unwrap().with_github(github)
+  }
+
+  // SHA-256 digest of "archive" string, used as test assets content
+  static ARCHIVE_DIGEST: &str = "0eb3e36bfb24dcd9bb1d1bece1531216b59539a8fde17ee80224af0653c92aa3";
 
   #[test]
-  fn check_version_exists_success() {
-    assert!(check_version_exists("22.0", &None).is_ok());
-    assert!(check_version_exists("3.7.0", &None).is_ok());
+  fn get_release_success() {
+    let github = GitHub::from_env().unwrap();
+    assert!(get_release(&github, "22.0", &None, &settings(&github)).is_ok());
+    assert!(get_release(&github, "3.7.0", &None, &settings(&github)).is_ok());
   }
 
   #[test]
-  fn check_version_exists_fail() {
-    let result = check_version_exists("0.1.0", &None);
+  fn get_release_fail() {
+    let github = GitHub::from_env().unwrap();
+    let result = get_release(&github, "0.1.0", &None, &settings(&github));
     assert!(result.is_err());
     assert!(matches!(result.unwrap_err(), Error::NonExistsVersion { .. }));
   }
 
+  #[test]
+  fn asset_url() {
+    let github = GitHub::from_env().unwrap();
+    assert_eq!(
+      get_asset_url(&github, &None, "22.0", "protoc-22.0-linux-x86_64.zip"),
+      concat!(
+        "https://github.com/protocolbuffers/protobuf/releases/download/",
+        "v22.0/protoc-22.0-linux-x86_64.zip"
+      )
+    );
+    assert_eq!(
+      get_asset_url(
+        &github, &Some(String::from("https://mirror.corp.example/protobuf")), "22.0", "a.zip"
+      ),
+      "https://mirror.corp.example/protobuf/v22.0/a.zip"
+    );
+  }
+
+  #[test]
+  fn download_from_mirror_fail_version() {
+    let server = TestServer::new(vec![response(404, &[], b"")]);
+    let github = GitHub::from_env().unwrap();
+    let url = get_asset_url(
+      &github, &Some(server.url("/protobuf")), "22.0", "protoc-22.0-win64.zip"
+    );
+
+    let result = download("22.0", "win64", &url, &None, &settings(&github));
+    assert!(result.is_err());
+    assert!(matches!(
+      result.unwrap_err(),
+      Error::NonExistsPlatformVersion((version, platform))
+        if version == "22.0" && platform == "win64"
+    ));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with("GET /protobuf/v22.0/protoc-22.0-win64.zip "));
+  }
+
+  // Release API response with passed assets names, which download URLs point to passed server
+  fn release_json(server: &TestServer, names: &[&str]) -> Vec<u8> {
+    let assets: Vec<String> = names
+      .iter()
+      .map(|name| format!(
+        r#"{{"name":"{}","browser_download_url":"{}","digest":"sha256:{}"}}"#,
+        name, server.url(&format!("/d/{}", name)), ARCHIVE_DIGEST
+      ))
+      .collect();
+
+    format!(r#"{{"tag_name":"v3.7.0rc1","assets":[{}]}}"#, assets.join(",")).into_bytes()
+  }
+
+  #[test]
+  fn download_asset_from_release_listing() {
+    let assets = TestServer::new(vec![response(200, &[], b"archive")]);
+    let body = release_json(
+      &assets, &["protoc-3.7.0-rc1-linux-x86_64.zip", "protoc-3.7.0-rc1-win64.zip"]
+    );
+    let server = TestServer::new(vec![response(404, &[], b""), response(200, &[], &body)]);
+    let github = GitHub::new(Some(server.url("")), Some(server.url("/api")), None).unwrap();
+
+    let names = vec![String::from("protoc-3.7.0-rc-1-linux-x86_64.zip")];
+    let result = download_asset(
+      &github, &None, "3.7.0rc1", &names, &None, false, &settings(&github)
+    );
+    assert!(result.is_ok());
+
+    let (response, digest, file_name) = result.unwrap();
+    assert_eq!(response.into_string().unwrap(), "archive");
+    assert_eq!(digest, Some(format!("sha256:{}", ARCHIVE_DIGEST)));
+    assert_eq!(file_name, "protoc-3.7.0-rc1-linux-x86_64.zip");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].starts_with("GET /protocolbuffers/protobuf/releases/download/"));
+    assert!(requests[1].starts_with("GET /api/repos/protocolbuffers/protobuf/releases/tags/"));
+    assert!(assets.requests()[0].starts_with("GET /d/protoc-3.7.0-rc1-linux-x86_64.zip "));
+  }
+
+  #[test]
+  fn download_asset_fallbacks() {
+    let mirror = TestServer::new(vec![
+      response(404, &[], b""),
+      response(404, &[], b""),
+      response(200, &[], b"archive")
+    ]);
+    let github = GitHub::new(None, None, None).unwrap();
+
+    let names: Vec<String> = [
+      "protoc-3.19.4-osx-aarch_64.zip",
+      "protoc-3.19.4-osx-universal_binary.zip",
+      "protoc-3.19.4-osx-x86_64.zip"
+    ].iter().map(|name| name.to_string()).collect();
+    let result = download_asset(
+      &github, &Some(mirror.url("")), "3.19.4", &names, &None, false, &settings(&github)
+    );
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().2, "protoc-3.19.4-osx-x86_64.zip");
+    assert_eq!(mirror.requests().len(), 3);
+
+    // Release listing used with same preference order
+    let assets = TestServer::new(vec![response(200, &[], b"archive")]);
+    let body = release_json(
+      &assets, &["protoc-22.0-osx-x86_64.zip", "protoc-22.0-osx-universal_binary.zip"]
+    );
+    let server = TestServer::new(vec![
+      response(404, &[], b""),
+      response(404, &[], b""),
+      response(404, &[], b""),
+      response(200, &[], &body)
+    ]);
+    let github = GitHub::new(Some(server.url("")), Some(server.url("/api")), None).unwrap();
+
+    let names: Vec<String> = names.iter().map(|name| name.replace("3.19.4", "22.0")).collect();
+    let result = download_asset(&github, &None, "22.0", &names, &None, false, &settings(&github));
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().2, "protoc-22.0-osx-universal_binary.zip");
+    assert!(assets.requests()[0].starts_with("GET /d/protoc-22.0-osx-universal_binary.zip "));
+  }
+
+  #[test]
+  fn download_asset_non_exists_platform() {
+    let assets = TestServer::new(vec![]);
+    let body = release_json(&assets, &["protoc-22.0-win64.zip", "protoc-22.0-linux-x86_64.zip"]);
+    let server = TestServer::new(vec![response(404, &[], b""), response(200, &[], &body)]);
+    let github = GitHub::new(Some(server.url("")), Some(server.url("/api")), None).unwrap();
+
+    let names = vec![String::from("protoc-22.0-osx-aarch_64.zip")];
+    let result = download_asset(&github, &None, "22.0", &names, &None, false, &settings(&github));
+    assert!(matches!(
+      result.unwrap_err(),
+      Error::NonExistsPlatformAsset((version, platform, platforms))
+        if version == "22.0" && platform == "osx-aarch_64" &&
+          platforms == vec!["linux-x86_64", "win64"]
+    ));
+  }
+
+  #[test]
+  fn requested_platform() {
+    let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(get_requested_platform(&names(&["protoc-22.0-osx-aarch_64.zip"])), "osx-aarch_64");
+    assert_eq!(get_requested_platform(&names(&["protoc-22.0-win64.tar.xz", "a.zip"])), "win64");
+    assert_eq!(get_requested_platform(&names(&["protobuf-22.0.zip"])), "protobuf-22.0.zip");
+    assert_eq!(get_requested_platform(&[]), "");
+
+    assert_eq!(strip_asset_extension("protoc-22.0-win64.tar.xz"), "protoc-22.0-win64");
+    assert_eq!(strip_asset_extension("protoc-22.0-win64"), "protoc-22.0-win64");
+  }
+
+  #[test]
+  fn download_asset_verify_digest_from_mirror() {
+    let mirror = TestServer::new(vec![response(200, &[], b"archive")]);
+    let assets = TestServer::new(vec![]);
+    let body = release_json(&assets, &["protoc-22.0-win64.zip"]);
+    let server = TestServer::new(vec![response(200, &[], &body)]);
+    let github = GitHub::new(Some(server.url("")), Some(server.url("/api")), None).unwrap();
+
+    // Mirror formats other than zip can't be verified, so zip asset downloaded
+    let names = vec![
+      String::from("protoc-22.0-win64.tar.gz"), String::from("protoc-22.0-win64.zip")
+    ];
+    let result = download_asset(
+      &github, &Some(mirror.url("/protobuf")), "22.0", &names, &None, true, &settings(&github)
+    );
+    let (_, digest, file_name) = result.unwrap();
+    assert_eq!(digest, Some(format!("sha256:{}", ARCHIVE_DIGEST)));
+    assert_eq!(file_name, "protoc-22.0-win64.zip");
+
+    // Release metadata fetched from GitHub API, asset downloaded from mirror
+    assert!(server.requests()[0].starts_with("GET /api/repos/protocolbuffers/protobuf/releases/"));
+    assert!(mirror.requests()[0].starts_with("GET /protobuf/v22.0/protoc-22.0-win64.zip "));
+    assert!(assets.requests().is_empty());
+  }
+
+  #[test]
+  fn download_asset_verify_digest_not_published() {
+    let names = vec![String::from("protoc-22.0-win64.zip")];
+
+    for (digest, expected) in [
+      (String::new(), None), (String::from(r#","digest":"sha1:abc""#), Some("sha1:abc"))
+    ] {
+      let assets = TestServer::new(vec![]);
+      let asset = format!(
+        r#"{{"name":"protoc-22.0-win64.zip","browser_download_url":"{}"{}}}"#,
+        assets.url("/d/protoc-22.0-win64.zip"), digest
+      );
+      let body = format!(r#"{{"tag_name":"v22.0","assets":[{}]}}"#, asset);
+      let server = TestServer::new(vec![response(200, &[], body.as_bytes())]);
+      let github = GitHub::new(Some(server.url("")), Some(server.url("/api")), None).unwrap();
+
+      let result = download_asset(&github, &None, "22.0", &names, &None, true, &settings(&github));
+      assert!(matches!(
+        result.unwrap_err(),
+        Error::MissingDigest((file_name, digest))
+          if file_name == "protoc-22.0-win64.zip" && digest.as_deref() == expected
+      ));
+
+      // Unverifiable asset not downloaded
+      assert!(assets.requests().is_empty());
+    }
+  }
+
+  #[test]
+  fn sha256_digest() {
+    let mut reader = HashingReader::new("archive".as_bytes());
+    std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+    assert_eq!(reader.finish().0, ARCHIVE_DIGEST);
+  }
+
+  #[test]
+  fn read_asset_to_memory() {
+    let digest = Some(format!("sha256:{}", ARCHIVE_DIGEST));
+
+    let result = read_asset(Response::new(200, "OK", "archive").unwrap(), "a.zip", &digest, 7);
+    let (buffer, timings) = result.unwrap();
+    assert_eq!(buffer.into_inner(), b"archive");
+    assert_eq!(timings.size, 7);
+
+    let result = read_asset(Response::new(200, "OK", "archive").unwrap(), "a.zip", &digest, 6);
+    assert!(matches!(result.unwrap_err(), Error::Io { .. }));
+
+    let result = read_asset(Response::new(200, "OK", "modified").unwrap(), "a.zip", &digest, 100);
+    assert!(matches!(
+      result.unwrap_err(), Error::Checksum((file_name, _, _)) if file_name == "a.zip"
+    ));
+  }
+
+  #[test]
+  fn unpack_asset_remove_temporary_file() {
+    let dir = create_test_dir("unpack_asset_remove_temporary_file");
+
+    let response = Response::new(200, "OK", "not zip").unwrap();
+    let result = unpack_asset(response, "a.zip", &None, &dir.0, &dir.0.join("a"), None);
+    assert!(matches!(result.unwrap_err(), Error::Zip { .. }));
+    assert!(!dir.0.join("a.zip.download").exists());
+    assert!(!dir.0.join("a").exists());
+
+    let digest = Some(format!("sha256:{}", ARCHIVE_DIGEST));
+    let response = Response::new(200, "OK", "modified").unwrap();
+    let result = unpack_asset(response, "a.zip", &digest, &dir.0, &dir.0.join("a"), None);
+    assert!(matches!(
+      result.unwrap_err(), Error::Checksum((file_name, _, _)) if file_name == "a.zip"
+    ));
+    assert!(!dir.0.join("a.zip.download").exists());
+  }
+
+  #[test]
+  fn write_asset_digest_check() {
+    let dir = create_test_dir("write_asset_digest_check");
+    let path = dir.0.join("protoc-22.0-win64.zip");
+    let write_asset = |content: &str, digest: &Option<String>| {
+      let response = Response::new(200, "OK", content).unwrap();
+      write_named_asset(response, &path, "protoc-22.0-win64.zip", digest)
+    };
+
+    let digest = Some(format!("sha256:{}", ARCHIVE_DIGEST));
+    let result = write_asset("archive", &digest);
+    assert!(result.is_ok());
+    assert!(path.exists());
+
+    let result = write_asset("modified", &digest);
+    assert!(matches!(
+      result.unwrap_err(),
+      Error::Checksum((file_name, expected, _))
+        if file_name == "protoc-22.0-win64.zip" && expected == ARCHIVE_DIGEST
+    ));
+    assert!(!path.exists());
+
+    // Unknown digest algorithms ignored
+    let digest = Some(String::from("sha512:00"));
+    let result = write_asset("modified", &digest);
+    assert!(result.is_ok());
+  }
+
+  #[test]
+  fn download_from_mirror_fail_status() {
+    let server = TestServer::new(vec![response(403, &[], b"forbidden")]);
+    let github = GitHub::from_env().unwrap();
+    let url = get_asset_url(
+      &github, &Some(server.url("/protobuf")), "22.0", "protoc-22.0-win64.zip"
+    );
+
+    let result = download("22.0", "win64", &url, &None, &settings(&github));
+    assert!(matches!(
+      result.unwrap_err(),
+      Error::Ureq(err) if matches!(*err, ureq::Error::Status(403, _))
+    ));
+  }
+
   #[test]
   fn download_success() {
-    let result = download("2.4.1", &None, "protoc-2.4.1-win32.zip");
+    let github = GitHub::from_env().unwrap();
+    let url = get_asset_url(&github, &None, "2.4.1", "protoc-2.4.1-win32.zip");
+    let result = download("2.4.1", "win32", &url, &None, &settings(&github));
     assert!(result.is_ok());
   }
 
   #[test]
   fn download_fail_version() {
     // Version 3.19.4 has not yet been pre-builded for Apple M1
-    let result = download("3.19.4", &None, "protoc-3.19.4-osx-aarch_64.zip");
+    let github = GitHub::from_env().unwrap();
+    let url = get_asset_url(&github, &None, "3.19.4", "protoc-3.19.4-osx-aarch_64.zip");
+    let result = download("3.19.4", "osx-aarch_64", &url, &None, &settings(&github));
     assert!(result.is_err());
     assert!(matches!(result.unwrap_err(), Error::NonExistsPlatformVersion { .. }));
   }
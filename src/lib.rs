@@ -1,12 +1,33 @@
 #![doc = include_str!("../readme.md")]
 
+mod archive;
+mod credentials;
 mod error;
+mod fallback;
 mod force;
+mod github;
 mod helpers;
 mod init;
 mod install;
+mod maven;
 mod path;
+mod progress;
+mod proxy;
+mod release;
 mod request;
+#[cfg(feature = "build-from-source")]
+mod source;
+#[cfg(test)]
+mod test_dir;
+#[cfg(test)]
+mod test_server;
+mod tls;
 mod version;
 
-pub use { error::Error, init::init };
\ No newline at end of file
+pub use {
+  error::Error,
+  init::{ Installation, init, init_for_platform, init_installation },
+  progress::{
+    DownloadProgress, Progress, Timings, clear_progress_callback, set_progress_callback
+  }
+};
\ No newline at end of file
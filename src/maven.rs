@@ -0,0 +1,411 @@
+use std::{
+  env::var,
+  fs::{ create_dir_all, File },
+  io::{ Seek, SeekFrom },
+  path::Path,
+  time::Instant
+};
+use sha1::Sha1;
+use sha2::Sha256;
+use crate::{
+  archive::{ install_staged, set_executable },
+  error::Error,
+  install::{ calculate_digest, download, write_named_asset },
+  path::get_bin_path,
+  progress::report_finish,
+  release::get_asset_platform,
+  request::{ Settings, request_with_token }
+};
+
+// Maven Central repository URL
+static MAVEN_CENTRAL_URL: &str = "https://repo.maven.apache.org/maven2";
+
+// Protobuf compiler artifact path in Maven repository
+static PROTOC_ARTIFACT_PATH: &str = "com/google/protobuf/protoc";
+
+// Artifact checksum algorithm, published by Maven repository in file near artifact
+#[derive(Debug, PartialEq)]
+pub(crate) enum ChecksumAlgorithm {
+  Sha256,
+  Sha1
+}
+
+// Get Maven repository URL from `PROTOC_PREBUILT_MAVEN_URL` environment variable,
+// default is Maven Central
+fn get_maven_url() -> String {
+  var("PROTOC_PREBUILT_MAVEN_URL")
+    .ok()
+    .map(|value| value.trim().trim_end_matches('/').to_string())
+    .filter(|value| !value.is_empty())
+    .unwrap_or_else(|| String::from(MAVEN_CENTRAL_URL))
+}
+
+// Get Maven artifact version by protobuf repository tag version
+//
+// Since "21.0" Maven artifacts versions have major version prefix, "3." before "26.0"
+// and "4." after it, release candidates versions have `-rc-$NUMBER` suffix
+pub(crate) fn get_maven_version(version: &str) -> String {
+  let (main, rc) = match version.split_once("rc") {
+    Some((main, rc)) => {
+      (main.trim_end_matches(['-', '.']), Some(rc.trim_start_matches(['-', '.'])))
+    },
+    None => (version, None)
+  };
+
+  let major = main.split('.').next().and_then(|major| major.parse::<u64>().ok()).unwrap_or(0);
+  let main = match major {
+    21..=25 => format!("3.{}", main),
+    26.. => format!("4.{}", main),
+    _ => main.to_string()
+  };
+
+  match rc {
+    Some(rc) => format!("{}-rc-{}", main, rc),
+    None => main
+  }
+}
+
+// Get protobuf compiler artifact classifier for platform by `std::env::consts::OS`
+// and `std::env::consts::ARCH` values
+pub(crate) fn get_maven_classifier(os: &str, arch: &str) -> Option<&'static str> {
+  match (os, arch) {
+    ("linux", "aarch64") => Some("linux-aarch_64"),
+    ("linux", "powerpc64") => Some("linux-ppcle_64"),
+    ("linux", "s390x") => Some("linux-s390_64"),
+    ("linux", "x86") => Some("linux-x86_32"),
+    ("linux", "x86_64") => Some("linux-x86_64"),
+    ("macos", "aarch64") => Some("osx-aarch_64"),
+    ("macos", "x86_64") => Some("osx-x86_64"),
+    ("windows", "aarch64") => Some("windows-aarch_64"),
+    ("windows", "x86") => Some("windows-x86_32"),
+    ("windows", "x86_64") => Some("windows-x86_64"),
+    _ => None
+  }
+}
+
+// Get protobuf compiler artifact classifier for GitHub pre-built binaries asset name,
+// universal macOS binaries are not published in Maven repository
+fn get_asset_maven_classifier(protoc_asset_name: &str) -> Option<String> {
+  let protoc_asset_file_name = format!("{}.zip", protoc_asset_name);
+  let platform = get_asset_platform(&protoc_asset_file_name)?;
+
+  match platform {
+    "win32" => Some(String::from("windows-x86_32")),
+    "win64" => Some(String::from("windows-x86_64")),
+    "linux-x86-32" => Some(String::from("linux-x86_32")),
+    "linux-s390x" | "linux-s390x_64" => Some(String::from("linux-s390_64")),
+    "osx-universal_binary" => None,
+    _ => Some(platform.to_string())
+  }
+}
+
+// Get protobuf compiler artifacts classifiers in preference order, native platform
+// classifier (if passed) is first, next are classifiers for GitHub assets names
+pub(crate) fn get_maven_classifiers(
+  protoc_asset_names: &[String], native_classifier: Option<&str>
+) -> Vec<String> {
+  let mut classifiers: Vec<String> = native_classifier.map(String::from).into_iter().collect();
+
+  for classifier in protoc_asset_names.iter().filter_map(|name| get_asset_maven_classifier(name)) {
+    if !classifiers.contains(&classifier) {
+      classifiers.push(classifier);
+    }
+  }
+
+  classifiers
+}
+
+// Get installation directory name for protobuf compiler artifact from Maven repository
+pub(crate) fn get_maven_asset_name(version: &str, classifier: &str) -> String {
+  format!("protoc-{}-maven-{}", version, classifier)
+}
+
+// Format protobuf compiler artifact URL, artifact is binary file with `.exe` extension
+// for all platforms
+pub(crate) fn get_artifact_url(repository: &str, maven_version: &str, classifier: &str) -> String {
+  format!(
+    "{}/{}/{}/protoc-{}-{}.exe",
+    repository, PROTOC_ARTIFACT_PATH, maven_version, maven_version, classifier
+  )
+}
+
+// Parse checksum file content, it may contain file name after hex digest
+pub(crate) fn parse_checksum(content: &str, algorithm: &ChecksumAlgorithm) -> Option<String> {
+  let length = match algorithm {
+    ChecksumAlgorithm::Sha256 => 64,
+    ChecksumAlgorithm::Sha1 => 40
+  };
+
+  content
+    .split_whitespace()
+    .next()
+    .map(|hex| hex.to_lowercase())
+    .filter(|hex| hex.len() == length && hex.chars().all(|char| char.is_ascii_hexdigit()))
+}
+
+// Fetch artifact checksum, SHA-256 preferred, SHA-1 checksum file is required
+// by Maven Central, so it always published
+fn get_checksum(
+  url: &str, settings: &Settings
+) -> Result<(ChecksumAlgorithm, String), Error<'static>> {
+  let algorithms = [(ChecksumAlgorithm::Sha256, "sha256"), (ChecksumAlgorithm::Sha1, "sha1")];
+
+  for (algorithm, extension) in algorithms {
+    let checksum_url = format!("{}.{}", url, extension);
+
+    let content = match request_with_token(&checksum_url, &None, settings) {
+      Ok(response) => response.into_string().map_err(Error::Io)?,
+      Err(Error::Ureq(err)) if matches!(*err, ureq::Error::Status(404, _)) => continue,
+      Err(err) => return Err(err)
+    };
+
+    return parse_checksum(&content, &algorithm)
+      .map(|checksum| (algorithm, checksum))
+      .ok_or_else(|| Error::Maven(format!("invalid checksum file `{}` content", checksum_url)))
+  }
+
+  Err(Error::Maven(format!("checksum files for `{}` not found", url)))
+}
+
+// Inner testable logic of artifact installation, repository URL is passed
+//
+// Download and verify protobuf compiler artifact for first found classifier, binary written
+// to installation directory with same as pre-built binaries assets structure, return installed
+// asset name (installation directory name) and artifact classifier
+//
+// Artifacts don't contain includes, so `include` directory is empty
+fn install_from_repository<'a>(
+  repository: &str,
+  version: &'a str,
+  out_dir: &Path,
+  classifiers: &[String],
+  settings: &Settings
+) -> Result<(String, Option<String>), Error<'a>> {
+  let maven_version = get_maven_version(version);
+
+  for classifier in classifiers {
+    let url = get_artifact_url(repository, &maven_version, classifier);
+
+    let response = match download(version, classifier, &url, &None, settings) {
+      Err(Error::NonExistsPlatformVersion(_)) => continue,
+      result => result?
+    };
+    let (algorithm, expected) = get_checksum(&url, settings)?;
+
+    let protoc_asset_name = get_maven_asset_name(version, classifier);
+    let os = match classifier.starts_with("windows-") {
+      true => "windows",
+      false => "linux"
+    };
+    let file_name = format!("protoc-{}-{}.exe", maven_version, classifier);
+
+    // Binary verified before installation directory created, so failed download isn't used
+    let timings = install_staged(&out_dir.join(&protoc_asset_name), |protoc_out_dir| {
+      let protoc_bin = get_bin_path(version, os, protoc_out_dir);
+      create_dir_all(protoc_bin.parent().unwrap_or(protoc_out_dir)).map_err(Error::Io)?;
+      create_dir_all(protoc_out_dir.join("include")).map_err(Error::Io)?;
+
+      let (mut file, mut timings) = write_named_asset(response, &protoc_bin, &file_name, &None)?;
+
+      let started = Instant::now();
+      file.seek(SeekFrom::Start(0)).map_err(Error::Io)?;
+      let actual = match algorithm {
+        ChecksumAlgorithm::Sha256 => calculate_digest::<Sha256, File>(&mut file)?,
+        ChecksumAlgorithm::Sha1 => calculate_digest::<Sha1, File>(&mut file)?
+      };
+      drop(file);
+
+      if actual != expected {
+        return Err(Error::Checksum((file_name.clone(), expected, actual)))
+      }
+      timings.verify += started.elapsed();
+
+      // Artifact is bare binary, so there is nothing to unpack
+      set_executable(&protoc_bin)?;
+      Ok(timings)
+    })?;
+    report_finish(timings);
+
+    return Ok((protoc_asset_name, Some(classifier.clone())))
+  }
+
+  // Preferred classifier is requested platform
+  Err(Error::NonExistsPlatformVersion((version, classifiers.first().cloned().unwrap_or_default())))
+}
+
+// Install protobuf compiler artifact from Maven repository, classifiers are tried in passed order
+pub(crate) fn install_maven<'a>(
+  version: &'a str, out_dir: &Path, classifiers: &[String], settings: &Settings
+) -> Result<(String, Option<String>), Error<'a>> {
+  install_from_repository(&get_maven_url(), version, out_dir, classifiers, settings)
+}
+
+#[cfg(test)]
+mod test {
+  use std::fs::read;
+  use crate::{
+    error::Error,
+    request::Settings,
+    test_dir::create_test_dir,
+    test_server::{ TestServer, response }
+  };
+  use super::{
+    ChecksumAlgorithm,
+    get_artifact_url,
+    get_checksum,
+    get_maven_classifiers,
+    get_maven_version,
+    install_from_repository,
+    parse_checksum
+  };
+
+  // SHA-256 and SHA-1 digests of "binary" string
+  static BINARY_SHA256: &str = "9a3a45d01531a20e89ac6ae10b0b0beb0492acd7216a368aa062d1a5fecaf9cd";
+  static BINARY_SHA1: &str = "7e57cfe843145135aee1f4d0d63ceb7842093712";
+
+  #[test]
+  fn maven_version() {
+    assert_eq!(get_maven_version("3.21.12"), "3.21.12");
+    assert_eq!(get_maven_version("21.12"), "3.21.12");
+    assert_eq!(get_maven_version("22.0"), "3.22.0");
+    assert_eq!(get_maven_version("25.5"), "3.25.5");
+    assert_eq!(get_maven_version("26.1"), "4.26.1");
+    assert_eq!(get_maven_version("22.0-rc3"), "3.22.0-rc-3");
+    assert_eq!(get_maven_version("3.21.0-rc1"), "3.21.0-rc-1");
+    assert_eq!(get_maven_version("3.0.0-beta-4"), "3.0.0-beta-4");
+  }
+
+  #[test]
+  fn maven_classifiers() {
+    let names: Vec<String> = [
+      "protoc-22.0-osx-aarch_64", "protoc-22.0-osx-universal_binary", "protoc-22.0-osx-x86_64"
+    ].iter().map(|name| name.to_string()).collect();
+    assert_eq!(
+      get_maven_classifiers(&names, Some("osx-aarch_64")), vec!["osx-aarch_64", "osx-x86_64"]
+    );
+
+    let names = vec![String::from("protoc-22.0-win64")];
+    assert_eq!(
+      get_maven_classifiers(&names, Some("windows-aarch_64")),
+      vec!["windows-aarch_64", "windows-x86_64"]
+    );
+    assert_eq!(get_maven_classifiers(&names, None), vec!["windows-x86_64"]);
+  }
+
+  #[test]
+  fn artifact_url() {
+    assert_eq!(
+      get_artifact_url("https://repo.maven.apache.org/maven2", "3.22.0", "linux-x86_64"),
+      concat!(
+        "https://repo.maven.apache.org/maven2/com/google/protobuf/protoc/3.22.0/",
+        "protoc-3.22.0-linux-x86_64.exe"
+      )
+    );
+  }
+
+  #[test]
+  fn checksum_parse() {
+    assert_eq!(
+      parse_checksum(&format!("{}  protoc.exe\n", BINARY_SHA1), &ChecksumAlgorithm::Sha1),
+      Some(String::from(BINARY_SHA1))
+    );
+    assert_eq!(
+      parse_checksum(&BINARY_SHA256.to_uppercase(), &ChecksumAlgorithm::Sha256),
+      Some(String::from(BINARY_SHA256))
+    );
+    assert_eq!(parse_checksum(BINARY_SHA1, &ChecksumAlgorithm::Sha256), None);
+    assert_eq!(parse_checksum("", &ChecksumAlgorithm::Sha1), None);
+  }
+
+  #[test]
+  fn checksum_fallback_to_sha1() {
+    let server = TestServer::new(vec![
+      response(404, &[], b""),
+      response(200, &[], BINARY_SHA1.as_bytes())
+    ]);
+    let settings = Settings::from_env().unwrap();
+
+    let result = get_checksum(&server.url("/protoc.exe"), &settings);
+    assert_eq!(result.unwrap(), (ChecksumAlgorithm::Sha1, String::from(BINARY_SHA1)));
+
+    let requests = server.requests();
+    assert!(requests[0].starts_with("GET /protoc.exe.sha256 "));
+    assert!(requests[1].starts_with("GET /protoc.exe.sha1 "));
+
+    let server = TestServer::new(vec![response(404, &[], b""), response(404, &[], b"")]);
+    let result = get_checksum(&server.url("/protoc.exe"), &settings);
+    assert!(matches!(result.unwrap_err(), Error::Maven { .. }));
+  }
+
+  #[test]
+  fn install_artifact() {
+    let dir = create_test_dir("install_maven");
+    let server = TestServer::new(vec![
+      response(404, &[], b""),
+      response(200, &[], b"binary"),
+      response(200, &[], BINARY_SHA256.as_bytes())
+    ]);
+
+    let classifiers = vec![String::from("windows-aarch_64"), String::from("windows-x86_64")];
+    let settings = Settings::from_env().unwrap();
+    let result = install_from_repository(
+      &server.url("/maven2"), "22.0", &dir.0, &classifiers, &settings
+    );
+    assert_eq!(
+      result.unwrap(),
+      (String::from("protoc-22.0-maven-windows-x86_64"), Some(String::from("windows-x86_64")))
+    );
+
+    let protoc_out_dir = dir.0.join("protoc-22.0-maven-windows-x86_64");
+    assert_eq!(read(protoc_out_dir.join("bin").join("protoc.exe")).unwrap(), b"binary");
+    assert!(protoc_out_dir.join("include").is_dir());
+
+    let requests = server.requests();
+    assert!(requests[0].starts_with(
+      "GET /maven2/com/google/protobuf/protoc/3.22.0/protoc-3.22.0-windows-aarch_64.exe "
+    ));
+    assert!(requests[2].starts_with(
+      "GET /maven2/com/google/protobuf/protoc/3.22.0/protoc-3.22.0-windows-x86_64.exe.sha256 "
+    ));
+  }
+
+  #[test]
+  fn install_artifact_checksum_mismatch() {
+    let dir = create_test_dir("install_maven_checksum");
+    let server = TestServer::new(vec![
+      response(200, &[], b"modified"),
+      response(404, &[], b""),
+      response(200, &[], BINARY_SHA1.as_bytes())
+    ]);
+
+    let classifiers = vec![String::from("linux-x86_64")];
+    let settings = Settings::from_env().unwrap();
+    let install = |url: &str| {
+      install_from_repository(url, "3.21.12", &dir.0, &classifiers, &settings)
+    };
+
+    let result = install(&server.url(""));
+    assert!(matches!(
+      result.unwrap_err(),
+      Error::Checksum((file_name, expected, _))
+        if file_name == "protoc-3.21.12-linux-x86_64.exe" && expected == BINARY_SHA1
+    ));
+    assert!(!dir.0.join("protoc-3.21.12-maven-linux-x86_64").exists());
+
+    // Connection closed before artifact end, truncated binary isn't left
+    let truncated = b"HTTP/1.1 200 Test\r\nConnection: close\r\nContent-Length: 100\r\n\r\nbin";
+    let server = TestServer::new(vec![
+      truncated.to_vec(),
+      response(200, &[], BINARY_SHA256.as_bytes())
+    ]);
+    let result = install(&server.url(""));
+    assert!(matches!(result.unwrap_err(), Error::Io { .. }));
+    assert!(!dir.0.join("protoc-3.21.12-maven-linux-x86_64").exists());
+    assert!(!dir.0.join("protoc-3.21.12-maven-linux-x86_64.unpack").exists());
+
+    // No one classifier found
+    let server = TestServer::new(vec![response(404, &[], b"")]);
+    let result = install(&server.url(""));
+    assert!(matches!(result.unwrap_err(), Error::NonExistsPlatformVersion { .. }));
+  }
+}
@@ -1,4 +1,4 @@
-use std::{ env::consts::OS, path::{ Path, PathBuf } };
+use std::path::{ Path, PathBuf };
 
 // GitHub protobuf asset structure information:
 // - binary file located in `bin` directory;
@@ -16,11 +16,11 @@ fn is_binary_in_root(version: &str) -> bool {
   version == "3.0.0-beta-3" || version == "3.0.0-beta-4"
 }
 
-// Generate binary path path by protoc version and out directory path,
+// Generate binary path path by protoc version, asset operating system and out directory path,
 // will be called if path to binary not set force
 //
 // For special variants of older versions return path to binary without `bin` subdirectory
-pub(crate) fn get_bin_path(version: &str, protoc_out_dir: &Path) -> PathBuf {
+pub(crate) fn get_bin_path(version: &str, os: &str, protoc_out_dir: &Path) -> PathBuf {
   let mut protoc_bin: PathBuf = protoc_out_dir.to_path_buf();
 
   // For old versions no need add `bin` part
@@ -29,7 +29,7 @@ pub(crate) fn get_bin_path(version: &str, protoc_out_dir: &Path) -> PathBuf {
   }
 
   // Add binary file name
-  protoc_bin.push(format!("protoc{}", match OS { "windows" => ".exe", _ => "" }));
+  protoc_bin.push(format!("protoc{}", match os { "windows" => ".exe", _ => "" }));
 
   protoc_bin
 }
@@ -62,7 +62,7 @@ mod test {
   #[test]
   fn with_bin_subdirectory() {
     assert_eq!(
-      get_bin_path("22.0", Path::new("/opt/protoc/22.0")),
+      get_bin_path("22.0", OS, Path::new("/opt/protoc/22.0")),
       match OS {
         "windows" => Path::new("/opt/protoc/22.0/bin/protoc.exe"),
         _ => Path::new("/opt/protoc/22.0/bin/protoc")
@@ -73,7 +73,7 @@ mod test {
   #[test]
   fn without_bin_subdirectory() {
     assert_eq!(
-      get_bin_path("2.4.1", Path::new("/opt/protoc/2.4.1")),
+      get_bin_path("2.4.1", OS, Path::new("/opt/protoc/2.4.1")),
       match OS {
         "windows" => Path::new("/opt/protoc/2.4.1/protoc.exe"),
         _ => Path::new("/opt/protoc/2.4.1/protoc")
@@ -81,6 +81,18 @@ mod test {
     );
   }
 
+  #[test]
+  fn other_os_binary() {
+    assert_eq!(
+      get_bin_path("22.0", "windows", Path::new("/opt/protoc/22.0")),
+      Path::new("/opt/protoc/22.0/bin/protoc.exe")
+    );
+    assert_eq!(
+      get_bin_path("22.0", "linux", Path::new("/opt/protoc/22.0")),
+      Path::new("/opt/protoc/22.0/bin/protoc")
+    );
+  }
+
   #[test]
   fn with_include_subdirectory() {
     assert_eq!(
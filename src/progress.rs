@@ -0,0 +1,298 @@
+use std::{
+  io::Read,
+  sync::{ Arc, Mutex },
+  time::{ Duration, Instant }
+};
+use crate::helpers::var_bool;
+
+// Minimum interval between progress callback calls
+static CALLBACK_INTERVAL: Duration = Duration::from_millis(250);
+
+// Minimum interval between progress `cargo:warning` lines
+static WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+// Progress callback type
+type Callback = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+// Progress callback set by library user
+static CALLBACK: Mutex<Option<Callback>> = Mutex::new(None);
+
+/// Asset download progress information
+#[derive(Clone, Debug, PartialEq)]
+pub struct DownloadProgress {
+  /// Downloaded asset file name
+  pub file_name: String,
+  /// Downloaded bytes count
+  pub downloaded: u64,
+  /// Asset size in bytes, `None` if server don't send it
+  pub total: Option<u64>,
+  /// Time elapsed from download start
+  pub elapsed: Duration
+}
+
+impl DownloadProgress {
+  /// Average download rate in bytes per second
+  pub fn rate(&self) -> f64 {
+    match self.elapsed.as_secs_f64() {
+      secs if secs > 0.0 => self.downloaded as f64 / secs,
+      _ => 0.0
+    }
+  }
+
+  /// Estimated time to download end, `None` if asset size or download rate unknown
+  pub fn eta(&self) -> Option<Duration> {
+    let total = self.total?;
+    let rate = Some(self.rate()).filter(|rate| *rate > 0.0)?;
+    Some(Duration::from_secs_f64(total.saturating_sub(self.downloaded) as f64 / rate))
+  }
+}
+
+/// Asset installation stages timings
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timings {
+  /// Installed asset file name
+  pub file_name: String,
+  /// Downloaded bytes count
+  pub size: u64,
+  /// Download time without digest calculation
+  pub download: Duration,
+  /// Digest calculation and verification time
+  pub verify: Duration,
+  /// Unpack time
+  pub extract: Duration
+}
+
+/// Asset installation progress event
+#[derive(Clone, Debug, PartialEq)]
+pub enum Progress {
+  /// Asset download in progress, also sent once after download end
+  Download(DownloadProgress),
+  /// Asset installed
+  Finish(Timings)
+}
+
+/// Set callback, which called with asset installation progress events, download progress
+/// events are sent not more often than every 250 milliseconds
+///
+/// Callback is global and used by all next installations, for example, in build script
+/// it can print progress with `cargo:warning=` lines or report it to build system.
+pub fn set_progress_callback<F>(callback: F)
+where
+  F: Fn(&Progress) + Send + Sync + 'static
+{
+  *CALLBACK.lock().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(callback));
+}
+
+/// Remove callback set by [`set_progress_callback`]
+pub fn clear_progress_callback() {
+  *CALLBACK.lock().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+// Call progress callback, if it set
+fn call(progress: &Progress) {
+  let callback = CALLBACK.lock().unwrap_or_else(|err| err.into_inner()).clone();
+  if let Some(callback) = callback {
+    callback(progress);
+  }
+}
+
+// Check is progress `cargo:warning` lines enabled
+fn is_warnings_enabled() -> bool {
+  var_bool("PROTOC_PREBUILT_PROGRESS")
+}
+
+// Format bytes count in binary units
+fn format_bytes(bytes: u64) -> String {
+  let units = ["B", "KiB", "MiB", "GiB"];
+  let mut value = bytes as f64;
+  let mut unit = 0;
+
+  while value >= 1024.0 && unit < units.len() - 1 {
+    value /= 1024.0;
+    unit += 1;
+  }
+
+  match unit {
+    0 => format!("{} B", bytes),
+    _ => format!("{:.1} {}", value, units[unit])
+  }
+}
+
+// Format download progress line, for example,
+// "protoc-22.0-linux-x86_64.zip: 1.5 MiB / 2.4 MiB, 512.0 KiB/s, ETA 2s"
+fn format_download(progress: &DownloadProgress) -> String {
+  let mut line = format!("{}: {}", progress.file_name, format_bytes(progress.downloaded));
+
+  if let Some(total) = progress.total {
+    line.push_str(&format!(" / {}", format_bytes(total)));
+  }
+  line.push_str(&format!(", {}/s", format_bytes(progress.rate() as u64)));
+  if let Some(eta) = progress.eta() {
+    line.push_str(&format!(", ETA {}s", eta.as_secs()));
+  }
+
+  line
+}
+
+// Format installation timings summary line
+fn format_timings(timings: &Timings) -> String {
+  format!(
+    "{}: downloaded {} in {:.2}s, verified in {:.2}s, unpacked in {:.2}s",
+    timings.file_name,
+    format_bytes(timings.size),
+    timings.download.as_secs_f64(),
+    timings.verify.as_secs_f64(),
+    timings.extract.as_secs_f64()
+  )
+}
+
+// Report installation finish to callback and build script output
+pub(crate) fn report_finish(timings: Timings) {
+  if is_warnings_enabled() {
+    println!("cargo:warning=protoc-prebuilt: {}", format_timings(&timings));
+  }
+  call(&Progress::Finish(timings));
+}
+
+// Reader wrapper, which report download progress to callback and build script output
+pub(crate) struct ProgressReader<R> {
+  inner: R,
+  progress: DownloadProgress,
+  started: Instant,
+  last_callback: Option<Instant>,
+  last_warning: Instant,
+  warnings: bool,
+  finished: bool
+}
+
+impl<R: Read> ProgressReader<R> {
+  pub(crate) fn new(inner: R, file_name: &str, total: Option<u64>) -> Self {
+    let started = Instant::now();
+
+    ProgressReader {
+      inner,
+      progress: DownloadProgress {
+        file_name: file_name.to_string(), downloaded: 0, total, elapsed: Duration::ZERO
+      },
+      started,
+      last_callback: None,
+      last_warning: started,
+      warnings: is_warnings_enabled(),
+      finished: false
+    }
+  }
+
+  fn report(&mut self, is_end: bool) {
+    let now = Instant::now();
+    self.progress.elapsed = now - self.started;
+
+    let is_callback_time = match self.last_callback {
+      Some(last) => now - last >= CALLBACK_INTERVAL,
+      None => true
+    };
+    if is_end || is_callback_time {
+      self.last_callback = Some(now);
+      call(&Progress::Download(self.progress.clone()));
+    }
+
+    if self.warnings && !is_end && now - self.last_warning >= WARNING_INTERVAL {
+      self.last_warning = now;
+      println!("cargo:warning=protoc-prebuilt: {}", format_download(&self.progress));
+    }
+  }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+  fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let size = self.inner.read(buffer)?;
+    self.progress.downloaded += size as u64;
+
+    if size == 0 && !self.finished {
+      self.finished = true;
+      self.report(true);
+    } else if size > 0 {
+      self.report(false);
+    }
+
+    Ok(size)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::{ io::{ copy, sink }, sync::{ Arc, Mutex }, time::Duration };
+  use super::{
+    DownloadProgress,
+    Progress,
+    ProgressReader,
+    Timings,
+    format_bytes,
+    format_download,
+    format_timings,
+    set_progress_callback
+  };
+
+  fn progress(downloaded: u64, total: Option<u64>, elapsed: u64) -> DownloadProgress {
+    DownloadProgress {
+      file_name: String::from("a.zip"), downloaded, total, elapsed: Duration::from_secs(elapsed)
+    }
+  }
+
+  #[test]
+  fn bytes_format() {
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+  }
+
+  #[test]
+  fn rate_and_eta() {
+    let value = progress(2048, Some(4096), 2);
+    assert_eq!(value.rate(), 1024.0);
+    assert_eq!(value.eta(), Some(Duration::from_secs(2)));
+
+    assert_eq!(progress(2048, None, 2).eta(), None);
+    assert_eq!(progress(0, Some(4096), 0).rate(), 0.0);
+    assert_eq!(progress(0, Some(4096), 0).eta(), None);
+  }
+
+  #[test]
+  fn lines_format() {
+    assert_eq!(
+      format_download(&progress(2048, Some(4096), 2)), "a.zip: 2.0 KiB / 4.0 KiB, 1.0 KiB/s, ETA 2s"
+    );
+    assert_eq!(format_download(&progress(100, None, 1)), "a.zip: 100 B, 100 B/s");
+
+    let timings = Timings {
+      file_name: String::from("a.zip"),
+      size: 2048,
+      download: Duration::from_millis(1500),
+      verify: Duration::from_millis(10),
+      extract: Duration::from_millis(250)
+    };
+    assert_eq!(
+      format_timings(&timings),
+      "a.zip: downloaded 2.0 KiB in 1.50s, verified in 0.01s, unpacked in 0.25s"
+    );
+  }
+
+  #[test]
+  fn reader_report() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let callback_events = events.clone();
+    set_progress_callback(move |progress| {
+      // Other tests may download assets in parallel
+      if let Progress::Download(progress) = progress {
+        if progress.file_name == "reader_report.zip" {
+          callback_events.lock().unwrap().push(progress.downloaded);
+        }
+      }
+    });
+
+    let mut reader = ProgressReader::new("content".as_bytes(), "reader_report.zip", Some(7));
+    copy(&mut reader, &mut sink()).unwrap();
+
+    // First chunk reported immediately, end reported once
+    assert_eq!(*events.lock().unwrap(), vec![7, 7]);
+  }
+}
@@ -0,0 +1,137 @@
+use serde::Deserialize;
+
+// GitHub release asset, contain only used fields
+#[derive(Debug, Deserialize)]
+pub(crate) struct Asset {
+  pub(crate) name: String,
+  pub(crate) browser_download_url: String,
+  // Provided only for assets uploaded after GitHub start calculate digests
+  #[serde(default)]
+  pub(crate) digest: Option<String>
+}
+
+// GitHub release API response, contain only used fields
+#[derive(Debug, Deserialize)]
+pub(crate) struct Release {
+  pub(crate) assets: Vec<Asset>
+}
+
+// GitHub repository tag, contain only used fields
+#[derive(Debug, Deserialize)]
+pub(crate) struct Tag {
+  pub(crate) name: String
+}
+
+// Markers of platform part start in protoc assets names, windows assets don't have
+// a separator between os and arch
+static ASSET_OS_MARKERS: [&str; 3] = ["-linux-", "-osx-", "-win"];
+
+// Get platform part of protoc asset file name, for example, "linux-x86_64"
+// for "protoc-22.0-linux-x86_64.zip", return None for non protoc or non zip assets
+pub(crate) fn get_asset_platform(name: &str) -> Option<&str> {
+  let name = name.strip_prefix("protoc-")?.strip_suffix(".zip")?;
+
+  ASSET_OS_MARKERS
+    .iter()
+    .filter_map(|marker| name.find(marker))
+    .min()
+    .map(|index| &name[index + 1..])
+}
+
+// Get SHA-256 hex digest from GitHub asset digest in `sha256:$HEX` view,
+// return None for other algorithms
+pub(crate) fn get_sha256_digest(digest: &str) -> Option<String> {
+  digest
+    .strip_prefix("sha256:")
+    .map(|hex| hex.trim().to_lowercase())
+    .filter(|hex| hex.len() == 64 && hex.chars().all(|char| char.is_ascii_hexdigit()))
+}
+
+impl Release {
+  // Find protoc asset for passed platform
+  pub(crate) fn find_asset(&self, platform: &str) -> Option<&Asset> {
+    self.assets.iter().find(|asset| get_asset_platform(&asset.name) == Some(platform))
+  }
+
+  // Platforms which protoc assets exist in release
+  pub(crate) fn platforms(&self) -> Vec<String> {
+    let mut platforms: Vec<String> = self.assets
+      .iter()
+      .filter_map(|asset| get_asset_platform(&asset.name))
+      .map(String::from)
+      .collect();
+
+    platforms.sort();
+    platforms.dedup();
+    platforms
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{ Asset, Release, get_asset_platform, get_sha256_digest };
+
+  fn release(names: &[&str]) -> Release {
+    Release {
+      assets: names
+        .iter()
+        .map(|name| Asset {
+          name: name.to_string(),
+          browser_download_url: format!("https://example.com/{}", name),
+          digest: None
+        })
+        .collect()
+    }
+  }
+
+  #[test]
+  fn asset_platform() {
+    assert_eq!(get_asset_platform("protoc-22.0-linux-x86_64.zip"), Some("linux-x86_64"));
+    assert_eq!(get_asset_platform("protoc-22.0-rc-3-osx-aarch_64.zip"), Some("osx-aarch_64"));
+    assert_eq!(get_asset_platform("protoc-21.12-win64.zip"), Some("win64"));
+    assert_eq!(
+      get_asset_platform("protoc-3.0.0-beta-4-linux-x86-32.zip"), Some("linux-x86-32")
+    );
+    assert_eq!(get_asset_platform("protoc-3.2.0rc2-linux-x86_64.zip"), Some("linux-x86_64"));
+    assert_eq!(get_asset_platform("protobuf-all-22.0.zip"), None);
+    assert_eq!(get_asset_platform("protoc-22.0-linux-x86_64.tar.gz"), None);
+  }
+
+  #[test]
+  fn find_asset() {
+    let release = release(&[
+      "protobuf-22.0.zip", "protoc-22.0-linux-x86_64.zip", "protoc-22.0-win64.zip"
+    ]);
+
+    let asset = release.find_asset("win64").unwrap();
+    assert_eq!(asset.name, "protoc-22.0-win64.zip");
+    assert_eq!(asset.browser_download_url, "https://example.com/protoc-22.0-win64.zip");
+    assert!(release.find_asset("osx-aarch_64").is_none());
+    assert_eq!(release.platforms(), vec!["linux-x86_64", "win64"]);
+  }
+
+  #[test]
+  fn sha256_digest() {
+    let hex = "5b5d2e1f8a3c0e0f7e42a6c1f7a0e2b6d7c3a9e8f1b2c3d4e5f60718293a4b5c";
+    assert_eq!(get_sha256_digest(&format!("sha256:{}", hex)).as_deref(), Some(hex));
+    assert_eq!(
+      get_sha256_digest(&format!("sha256:{}", hex.to_uppercase())).as_deref(), Some(hex)
+    );
+    assert_eq!(get_sha256_digest(&format!("sha512:{}", hex)), None);
+    assert_eq!(get_sha256_digest("sha256:abc"), None);
+  }
+
+  #[test]
+  fn deserialize_release() {
+    let json = r#"{"assets":[
+      {"name":"a.zip","browser_download_url":"https://a/a.zip","digest":null},
+      {"name":"b.zip","browser_download_url":"https://a/b.zip","digest":"sha256:00"},
+      {"name":"c.zip","browser_download_url":"https://a/c.zip"}
+    ]}"#;
+    let release: Release = ureq::Response::new(200, "OK", json).unwrap().into_json().unwrap();
+
+    assert_eq!(release.assets[0].digest, None);
+    assert_eq!(release.assets[1].digest.as_deref(), Some("sha256:00"));
+    assert_eq!(release.assets[2].digest, None);
+  }
+}
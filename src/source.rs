@@ -0,0 +1,239 @@
+use std::{
+  env::var,
+  ffi::OsString,
+  fs::{ create_dir_all, read_dir },
+  path::{ Path, PathBuf },
+  process::Command
+};
+use crate::{ error::Error, install::{ install_archive, install_source } };
+
+// Get protobuf source archives files names in preference order, since "22.0" release contain
+// only C++ sources archive, "21.x" releases named archives by protobuf "3.21.x" version,
+// older releases contain per language archives
+pub(crate) fn get_source_archive_names(version: &str) -> Vec<String> {
+  let mut names = vec![format!("protobuf-{}.zip", version)];
+
+  if version.starts_with("21.") {
+    names.push(format!("protobuf-cpp-3.{}.zip", version));
+  }
+  names.push(format!("protobuf-cpp-{}.zip", version));
+  names.push(format!("protobuf-all-{}.zip", version));
+
+  names
+}
+
+// Find directory with root `CMakeLists.txt` in unpacked source archive, archive content may be
+// placed in top level directory, before "22.0" CMake project located in `cmake` subdirectory
+pub(crate) fn find_cmake_dir(source_dir: &Path) -> Option<PathBuf> {
+  let mut dirs = vec![source_dir.to_path_buf()];
+
+  if let Ok(entries) = read_dir(source_dir) {
+    let mut subdirs: Vec<PathBuf> = entries
+      .filter_map(|entry| entry.ok())
+      .map(|entry| entry.path())
+      .filter(|path| path.is_dir())
+      .collect();
+    subdirs.sort();
+    dirs.extend(subdirs);
+  }
+
+  dirs
+    .into_iter()
+    .flat_map(|dir| [dir.clone(), dir.join("cmake")])
+    .find(|dir| dir.join("CMakeLists.txt").is_file())
+}
+
+// Inner testable logic of source preparation, user-provided archive path is passed
+//
+// Source directory is created only after successful unpack (see `install_staged`), so
+// existing directory is complete source tree, not one left by interrupted download or unpack
+fn prepare_source_from<'a>(
+  version: &'a str, out_dir: &Path, archive: Option<String>
+) -> Result<PathBuf, Error<'a>> {
+  let source_dir = out_dir.join(format!("protobuf-{}-source", version));
+  if source_dir.exists() {
+    return Ok(source_dir)
+  }
+
+  match archive {
+    Some(path) => install_archive(Path::new(path.trim()), &source_dir, None)?,
+    None => install_source(version, out_dir, &get_source_archive_names(version), &source_dir)?
+  }
+
+  Ok(source_dir)
+}
+
+// Unpack protobuf source archive from `PROTOC_PREBUILT_SOURCE_ARCHIVE` path or download it
+// from GitHub (or releases mirror), if it hasn't been done before
+fn prepare_source<'a>(version: &'a str, out_dir: &Path) -> Result<PathBuf, Error<'a>> {
+  let archive = var("PROTOC_PREBUILT_SOURCE_ARCHIVE")
+    .ok()
+    .filter(|value| !value.trim().is_empty());
+
+  prepare_source_from(version, out_dir, archive)
+}
+
+// Get CMake configure arguments, only compiler is built, it installed to passed directory
+// with same as pre-built binaries assets structure
+fn get_configure_args(cmake_dir: &Path, protoc_out_dir: &Path) -> Vec<OsString> {
+  let mut prefix = OsString::from("-DCMAKE_INSTALL_PREFIX=");
+  prefix.push(protoc_out_dir);
+
+  let mut args = vec![cmake_dir.as_os_str().to_os_string(), prefix];
+  args.extend([
+    "-DCMAKE_BUILD_TYPE=Release",
+    "-Dprotobuf_BUILD_TESTS=OFF",
+    "-Dprotobuf_BUILD_SHARED_LIBS=OFF",
+    "-Dprotobuf_BUILD_CONFORMANCE=OFF",
+    "-Dprotobuf_BUILD_EXAMPLES=OFF",
+    "-DABSL_PROPAGATE_CXX_STD=ON"
+  ].map(OsString::from));
+
+  args
+}
+
+// Run CMake in passed directory, its output is passed to build script output,
+// so build fail reason is printed by CMake itself, parallel build jobs count
+// is defined by cargo
+fn run_cmake(program: &str, args: &[OsString], dir: &Path) -> Result<(), Error<'static>> {
+  let mut command = Command::new(program);
+  command.args(args).current_dir(dir);
+  if let Ok(jobs) = var("NUM_JOBS") {
+    command.env("CMAKE_BUILD_PARALLEL_LEVEL", jobs);
+  }
+
+  let status = command
+    .status()
+    .map_err(|err| Error::SourceBuild(format!("run `{}` fail: {}", program, err)))?;
+
+  if !status.success() {
+    return Err(Error::SourceBuild(format!("`{}` exit with {}", program, status)))
+  }
+
+  Ok(())
+}
+
+// Build protobuf compiler from source by CMake and install it to passed directory with same
+// as pre-built binaries assets structure, CMake program can be defined by `CMAKE`
+// environment variable, same as `cmake` crate does it
+//
+// Binary built by default toolchain, so it built for build script host, which should run it
+pub(crate) fn build<'a>(
+  version: &'a str, out_dir: &Path, protoc_out_dir: &Path
+) -> Result<(), Error<'a>> {
+  let source_dir = prepare_source(version, out_dir)?;
+  let cmake_dir = find_cmake_dir(&source_dir).ok_or_else(|| {
+    Error::SourceBuild(String::from("`CMakeLists.txt` not found in protobuf source archive"))
+  })?;
+
+  let program = var("CMAKE").unwrap_or_else(|_| String::from("cmake"));
+  let build_dir = protoc_out_dir.join("build");
+  create_dir_all(&build_dir).map_err(Error::Io)?;
+
+  run_cmake(&program, &get_configure_args(&cmake_dir, protoc_out_dir), &build_dir)?;
+
+  let build_args = ["--build", ".", "--config", "Release", "--target", "install"];
+  run_cmake(&program, &build_args.map(OsString::from), &build_dir)?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use std::{ ffi::OsString, fs::{ create_dir_all, write, File }, io::Write, path::Path };
+  use zip::{ write::FileOptions, ZipWriter };
+  use crate::{ error::Error, test_dir::create_test_dir };
+  use super::{
+    find_cmake_dir,
+    get_configure_args,
+    get_source_archive_names,
+    prepare_source_from,
+    run_cmake
+  };
+
+  #[test]
+  fn source_archive_names() {
+    assert_eq!(get_source_archive_names("22.0"), vec![
+      "protobuf-22.0.zip", "protobuf-cpp-22.0.zip", "protobuf-all-22.0.zip"
+    ]);
+    assert_eq!(get_source_archive_names("21.12"), vec![
+      "protobuf-21.12.zip",
+      "protobuf-cpp-3.21.12.zip",
+      "protobuf-cpp-21.12.zip",
+      "protobuf-all-21.12.zip"
+    ]);
+  }
+
+  #[test]
+  fn cmake_dir() {
+    let dir = create_test_dir("find_cmake_dir");
+
+    // Top level directory
+    let root = dir.0.join("top");
+    create_dir_all(root.join("protobuf-22.0")).unwrap();
+    write(root.join("protobuf-22.0").join("CMakeLists.txt"), "").unwrap();
+    assert_eq!(find_cmake_dir(&root), Some(root.join("protobuf-22.0")));
+
+    // CMake project in `cmake` subdirectory
+    let root = dir.0.join("subdir");
+    create_dir_all(root.join("protobuf-3.21.12").join("cmake")).unwrap();
+    write(root.join("protobuf-3.21.12").join("cmake").join("CMakeLists.txt"), "").unwrap();
+    assert_eq!(find_cmake_dir(&root), Some(root.join("protobuf-3.21.12").join("cmake")));
+
+    // Archive content in root
+    let root = dir.0.join("root");
+    create_dir_all(&root).unwrap();
+    write(root.join("CMakeLists.txt"), "").unwrap();
+    assert_eq!(find_cmake_dir(&root), Some(root.clone()));
+
+    assert_eq!(find_cmake_dir(&dir.0.join("non_exists")), None);
+  }
+
+  #[test]
+  fn prepare_source_from_archive() {
+    let dir = create_test_dir("prepare_source");
+    let source_dir = dir.0.join("protobuf-22.0-source");
+
+    // Broken archive leave nothing, so next build retry unpack
+    let archive = dir.0.join("broken.zip");
+    write(&archive, "broken").unwrap();
+    let result = prepare_source_from("22.0", &dir.0, Some(archive.to_string_lossy().to_string()));
+    assert!(matches!(result.unwrap_err(), Error::Zip { .. }));
+    assert!(!source_dir.exists());
+
+    let archive = dir.0.join("protobuf-22.0.zip");
+    let mut writer = ZipWriter::new(File::create(&archive).unwrap());
+    writer.start_file("protobuf-22.0/CMakeLists.txt", FileOptions::default()).unwrap();
+    writer.write_all(b"").unwrap();
+    writer.finish().unwrap();
+
+    let result = prepare_source_from("22.0", &dir.0, Some(archive.to_string_lossy().to_string()));
+    assert_eq!(result.unwrap(), source_dir);
+    assert!(source_dir.join("CMakeLists.txt").is_file());
+  }
+
+  #[test]
+  fn configure_args() {
+    let args = get_configure_args(Path::new("source"), Path::new("out"));
+    assert_eq!(args[0], "source");
+    assert_eq!(args[1], "-DCMAKE_INSTALL_PREFIX=out");
+    assert!(args.contains(&OsString::from("-Dprotobuf_BUILD_TESTS=OFF")));
+  }
+
+  #[test]
+  fn cmake_run_fail() {
+    let dir = create_test_dir("cmake_run_fail");
+
+    let result = run_cmake("protoc-prebuilt-non-exists-cmake", &[], &dir.0);
+    assert!(matches!(result.unwrap_err(), Error::SourceBuild { .. }));
+
+    #[cfg(unix)]
+    {
+      let result = run_cmake("false", &[], &dir.0);
+      assert!(matches!(
+        result.unwrap_err(), Error::SourceBuild(message) if message.contains("exit")
+      ));
+      assert!(run_cmake("true", &[], &dir.0).is_ok());
+    }
+  }
+}
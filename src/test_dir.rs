@@ -0,0 +1,18 @@
+use std::{ env::temp_dir, fs::{ create_dir_all, remove_dir_all }, path::PathBuf };
+
+// Store directory path in struct to clear test artifacts in drop implementation
+pub(crate) struct DirectoryPath(pub(crate) PathBuf);
+
+impl Drop for DirectoryPath {
+  fn drop(&mut self) {
+    remove_dir_all(&self.0).unwrap();
+  }
+}
+
+// Create empty directory for test artifacts, directory left by previous run is cleared
+pub(crate) fn create_test_dir(name: &str) -> DirectoryPath {
+  let path = temp_dir().join("protoc-prebuilt-test").join(name);
+  remove_dir_all(&path).ok();
+  create_dir_all(&path).unwrap();
+  DirectoryPath(path)
+}
@@ -0,0 +1,71 @@
+use std::{
+  io::{ Read, Write },
+  net::TcpListener,
+  sync::{ Arc, Mutex },
+  thread::spawn
+};
+
+// Simple HTTP server for requests testing, sends passed responses to connections in order
+// and store received requests heads
+pub(crate) struct TestServer {
+  pub(crate) port: u16,
+  requests: Arc<Mutex<Vec<String>>>
+}
+
+impl TestServer {
+  pub(crate) fn new(responses: Vec<Vec<u8>>) -> Self {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let thread_requests = requests.clone();
+
+    spawn(move || {
+      for response in responses {
+        let (mut stream, _) = match listener.accept() {
+          Ok(connection) => connection,
+          Err(_) => return
+        };
+
+        // Read request head, requests in tests are sent without body
+        let mut head = Vec::new();
+        let mut buffer = [0; 1024];
+        while !head.ends_with(b"\r\n\r\n") {
+          match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(size) => head.extend_from_slice(&buffer[..size])
+          }
+        }
+
+        thread_requests.lock().unwrap().push(String::from_utf8_lossy(&head).to_string());
+        stream.write_all(&response).ok();
+      }
+    });
+
+    TestServer { port, requests }
+  }
+
+  pub(crate) fn url(&self, path: &str) -> String {
+    format!("http://127.0.0.1:{}{}", self.port, path)
+  }
+
+  pub(crate) fn requests(&self) -> Vec<String> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+// Build HTTP response with passed status, additional headers and body
+pub(crate) fn response(status: u16, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+  let mut response = format!(
+    "HTTP/1.1 {} Test\r\nConnection: close\r\nContent-Length: {}\r\n", status, body.len()
+  );
+
+  for (name, value) in headers {
+    response.push_str(&format!("{}: {}\r\n", name, value));
+  }
+  response.push_str("\r\n");
+
+  let mut bytes = response.into_bytes();
+  bytes.extend_from_slice(body);
+  bytes
+}
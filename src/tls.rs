@@ -0,0 +1,248 @@
+use std::{
+  env::{ var, split_paths },
+  fs::{ read, read_dir },
+  path::{ Path, PathBuf },
+  sync::Arc
+};
+use ureq::rustls::{
+  crypto::ring::default_provider,
+  pki_types::{ CertificateDer, pem::PemObject },
+  version::{ TLS12, TLS13 },
+  ClientConfig, RootCertStore
+};
+use crate::error::Error;
+
+// Environment variables contain path to additional root certificates bundle file,
+// `SSL_CERT_FILE` and `SSL_CERT_DIR` used same as OpenSSL does
+static CA_BUNDLE_FILE_VARS: [&str; 2] = ["CARGO_HTTP_CAINFO", "SSL_CERT_FILE"];
+static CA_BUNDLE_DIR_VAR: &str = "SSL_CERT_DIR";
+// Crate specific variable can contain path to file or directory
+static CA_BUNDLE_VAR: &str = "PROTOC_PREBUILT_CA_BUNDLE";
+
+// Parse all PEM certificates from passed file content,
+// return error if content can't be parsed or not contain certificates
+fn parse_ca_bundle(
+  path: &Path, content: &[u8]
+) -> Result<Vec<CertificateDer<'static>>, Error<'static>> {
+  let certs = CertificateDer::pem_slice_iter(content)
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|err| Error::CaBundle((path.display().to_string(), err.to_string())))?;
+
+  if certs.is_empty() {
+    return Err(Error::CaBundle((
+      path.display().to_string(), String::from("no PEM certificates found")
+    )))
+  }
+
+  Ok(certs)
+}
+
+// Read and parse certificates bundle file
+fn load_ca_bundle_file(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error<'static>> {
+  let content = read(path)
+    .map_err(|err| Error::CaBundle((path.display().to_string(), err.to_string())))?;
+
+  parse_ca_bundle(path, &content)
+}
+
+// Read and parse all certificates files in directory, files without certificates skipped,
+// because OpenSSL certificates directories usually contain other files
+fn load_ca_bundle_dir(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error<'static>> {
+  let entries = read_dir(path)
+    .map_err(|err| Error::CaBundle((path.display().to_string(), err.to_string())))?;
+
+  let mut certs = Vec::new();
+
+  for entry in entries {
+    let entry_path = entry
+      .map_err(|err| Error::CaBundle((path.display().to_string(), err.to_string())))?
+      .path();
+
+    if entry_path.is_file() {
+      if let Ok(entry_certs) = load_ca_bundle_file(&entry_path) {
+        certs.extend(entry_certs);
+      }
+    }
+  }
+
+  Ok(certs)
+}
+
+// Load certificates by path, which can point to file or directory
+fn load_ca_bundle(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error<'static>> {
+  if path.is_dir() {
+    load_ca_bundle_dir(path)
+  } else {
+    load_ca_bundle_file(path)
+  }
+}
+
+// Collect additional certificates bundles paths from environment variables with flag is
+// bundle required, only explicitly passed crate specific bundle is required, system wide
+// variables often list non exists paths (distros set several directories), so their
+// unreadable paths and invalid certificates are skipped
+fn get_ca_bundle_paths() -> Vec<(PathBuf, bool)> {
+  let mut paths: Vec<(PathBuf, bool)> = CA_BUNDLE_FILE_VARS
+    .iter()
+    .map(|key| (key, false))
+    .chain([(&CA_BUNDLE_VAR, true)])
+    .filter_map(|(key, is_required)| var(key).ok().map(|value| (value, is_required)))
+    .filter(|(value, _)| !value.trim().is_empty())
+    .map(|(value, is_required)| (PathBuf::from(value), is_required))
+    .collect();
+
+  // OpenSSL allow to pass several directories separated as in `PATH` variable
+  if let Ok(dirs) = var(CA_BUNDLE_DIR_VAR) {
+    paths.extend(
+      split_paths(&dirs)
+        .filter(|path| !path.as_os_str().is_empty())
+        .map(|path| (path, false))
+    );
+  }
+
+  paths
+}
+
+// Build TLS config contains default root certificates and certificates loaded by passed paths,
+// errors of not required bundles are ignored
+fn build_tls_config(
+  paths: &[(PathBuf, bool)]
+) -> Result<Option<Arc<ClientConfig>>, Error<'static>> {
+  if paths.is_empty() {
+    return Ok(None)
+  }
+
+  let mut roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+
+  for (path, is_required) in paths {
+    let certs = match load_ca_bundle(path) {
+      Ok(certs) => certs,
+      Err(_) if !is_required => continue,
+      Err(err) => return Err(err)
+    };
+
+    for cert in certs {
+      match roots.add(cert) {
+        Ok(_) => {},
+        Err(_) if !is_required => {},
+        Err(err) => return Err(Error::CaBundle((path.display().to_string(), err.to_string())))
+      }
+    }
+  }
+
+  // Same protocol versions and crypto provider as `ureq` use in default TLS config
+  let config = ClientConfig::builder_with_provider(default_provider().into())
+    .with_protocol_versions(&[&TLS12, &TLS13])
+    .map_err(|err| Error::CaBundle((String::new(), err.to_string())))?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+
+  Ok(Some(Arc::new(config)))
+}
+
+// Return TLS config with additional root certificates from environment variables,
+// for absence additional certificates return None to use `ureq` default config
+pub(crate) fn get_tls_config() -> Result<Option<Arc<ClientConfig>>, Error<'static>> {
+  build_tls_config(&get_ca_bundle_paths())
+}
+
+#[cfg(test)]
+mod test {
+  use std::{ env::temp_dir, fs::write, path::Path };
+  use crate::{ error::Error, test_dir::create_test_dir };
+  use super::{ build_tls_config, load_ca_bundle, parse_ca_bundle };
+
+  static CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBlTCCATugAwIBAgIUCU9QFRvFHqfbyYwImpVHhkl8lIowCgYIKoZIzj0EAwIw
+HzEdMBsGA1UEAwwUcHJvdG9jLXByZWJ1aWx0LXRlc3QwIBcNMjYxMDE4MTMyNTIz
+WhgPMjEyNjA5MjQxMzI1MjNaMB8xHTAbBgNVBAMMFHByb3RvYy1wcmVidWlsdC10
+ZXN0MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEbrpJZqyp7jjHDltxOLgZ+vXs
+/ktjALHtCB6AeSE+lKyqFwEHFFbEyz9kjdiWscxl1N/SEmpA24q7B8jWbm+9SaNT
+MFEwHQYDVR0OBBYEFB+1sF6jiuI0eVcBwb206qYuoBtXMB8GA1UdIwQYMBaAFB+1
+sF6jiuI0eVcBwb206qYuoBtXMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwID
+SAAwRQIgeYJlSoqycCxxeG8hJNaYKGjbArrjb4Hd0qRNzLUCejICIQD9b5AGR6Sf
+DEuO2rcOlTsOSfOEL1+PyQzYA/NQSxsGvA==
+-----END CERTIFICATE-----
+";
+
+  #[test]
+  fn parse_bundle_success() {
+    let result = parse_ca_bundle(Path::new("bundle.pem"), format!("{}{}", CERT, CERT).as_bytes());
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().len(), 2);
+  }
+
+  #[test]
+  fn parse_bundle_fail() {
+    let result = parse_ca_bundle(Path::new("bundle.pem"), b"not a certificate");
+    assert!(result.is_err());
+    assert!(matches!(
+      result.unwrap_err(),
+      Error::CaBundle((path, message)) if path == "bundle.pem" && message.contains("no PEM")
+    ));
+
+    let result = parse_ca_bundle(
+      Path::new("bundle.pem"), b"-----BEGIN CERTIFICATE-----\n!!!!\n-----END CERTIFICATE-----\n"
+    );
+    assert!(result.is_err());
+    assert!(matches!(result.unwrap_err(), Error::CaBundle { .. }));
+  }
+
+  #[test]
+  fn load_bundle_directory() {
+    let dir = create_test_dir("load_bundle_directory");
+    write(dir.0.join("first.pem"), CERT).unwrap();
+    write(dir.0.join("second.crt"), CERT).unwrap();
+    write(dir.0.join("readme.txt"), "not a certificate").unwrap();
+
+    let result = load_ca_bundle(&dir.0);
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap().len(), 2);
+  }
+
+  #[test]
+  fn load_bundle_non_exists_file() {
+    let result = load_ca_bundle(
+      &temp_dir().join("protoc-prebuilt-test").join("load_bundle_non_exists_file.pem")
+    );
+    assert!(result.is_err());
+    assert!(matches!(result.unwrap_err(), Error::CaBundle { .. }));
+  }
+
+  #[test]
+  fn build_config() {
+    let result = build_tls_config(&[]);
+    assert!(result.is_ok());
+    assert!(result.unwrap().is_none());
+
+    let dir = create_test_dir("build_config");
+    let bundle_path = dir.0.join("bundle.pem");
+    write(&bundle_path, CERT).unwrap();
+
+    let result = build_tls_config(&[(bundle_path, true)]);
+    assert!(result.is_ok());
+    assert!(result.unwrap().is_some());
+  }
+
+  #[test]
+  fn build_config_skip_system_paths() {
+    let dir = create_test_dir("build_config_skip_system_paths");
+    let bundle_path = dir.0.join("bundle.pem");
+    write(&bundle_path, CERT).unwrap();
+    let invalid_path = dir.0.join("invalid.pem");
+    write(&invalid_path, "not a certificate").unwrap();
+    let non_exists_path = dir.0.join("non_exists");
+
+    // System wide variables paths, like `SSL_CERT_DIR` directories, skipped on errors
+    let result = build_tls_config(&[
+      (non_exists_path.clone(), false), (invalid_path.clone(), false), (bundle_path, false)
+    ]);
+    assert!(result.unwrap().is_some());
+
+    // Explicitly passed bundle is required
+    let result = build_tls_config(&[(non_exists_path, true)]);
+    assert!(matches!(result.unwrap_err(), Error::CaBundle { .. }));
+    let result = build_tls_config(&[(invalid_path, true)]);
+    assert!(matches!(result.unwrap_err(), Error::CaBundle { .. }));
+  }
+}
@@ -1,4 +1,45 @@
-use crate::error::Error;
+use std::{ env::var, process::Command };
+use crate::{ error::Error, helpers::var_bool };
+
+// Prerelease prefixes used in protobuf repository tags
+static PRERELEASE_PREFIXES: [&str; 3] = ["rc", "alpha", "beta"];
+
+// Check is string is non empty ASCII digits sequence
+fn is_number(value: &str) -> bool {
+  !value.is_empty() && value.chars().all(|char| char.is_ascii_digit())
+}
+
+// Check is version string match protobuf repository tags (without `v` prefix) grammar:
+// two to four dot separated numbers with optional prerelease suffix, which contain optional
+// hyphen, one of `rc`, `alpha` or `beta` prefixes and optional number with optional
+// `.` or `-` delimiter, for example, "22.0", "3.21.12", "22.0-rc3", "3.7.0-rc.3",
+// "3.7.0rc2", "3.0.0-beta-4"
+pub(crate) fn validate_version(version: &str) -> Result<(), Error<'_>> {
+  let main_end = version
+    .find(|char: char| !char.is_ascii_digit() && char != '.')
+    .unwrap_or(version.len());
+  let (main, suffix) = version.split_at(main_end);
+
+  let parts: Vec<&str> = main.split('.').collect();
+  let is_valid_main = (2..=4).contains(&parts.len()) && parts.iter().all(|part| is_number(part));
+
+  let is_valid_suffix = suffix.is_empty() || {
+    let suffix = suffix.strip_prefix('-').unwrap_or(suffix);
+
+    PRERELEASE_PREFIXES.iter().any(|prefix| match suffix.strip_prefix(prefix) {
+      Some(number) => {
+        let number = number.strip_prefix(['.', '-']).unwrap_or(number);
+        number.is_empty() || is_number(number)
+      },
+      None => false
+    })
+  };
+
+  match is_valid_main && is_valid_suffix {
+    true => Ok(()),
+    false => Err(Error::InvalidVersion(version))
+  }
+}
 
 // In protobuf repository for release cadidate versions used "v22.0-rc3" tag name,
 // for example, but in asset name $VERSION part looks like "22.0-rc-3"
@@ -162,10 +203,251 @@ pub(crate) fn get_protoc_asset_name<'a>(
   ))
 }
 
+// Maximum count of suggested versions
+static MAX_SUGGESTIONS: usize = 5;
+
+// Maximum edit distance for suggested versions
+static MAX_SUGGESTION_DISTANCE: usize = 2;
+
+// Get numeric parts of version main part (before prerelease suffix),
+// for example, [3, 21, 12] for "3.21.12" and [22, 0] for "22.0-rc3"
+fn get_version_numbers(version: &str) -> Vec<u64> {
+  version
+    .split('.')
+    .map_while(|part| {
+      let digits: String = part.chars().take_while(|char| char.is_ascii_digit()).collect();
+      let number = digits.parse::<u64>().ok()?;
+      // Stop after part with prerelease suffix, like "0rc2" or "0-rc3"
+      Some((number, digits.len() == part.len()))
+    })
+    .scan(true, |is_continue, (number, is_full)| {
+      let current = *is_continue;
+      *is_continue = is_full;
+      current.then_some(number)
+    })
+    .collect()
+}
+
+// Check is version without prerelease suffix
+fn is_stable_version(version: &str) -> bool {
+  version.chars().all(|char| char.is_ascii_digit() || char == '.')
+}
+
+// Levenshtein distance between strings
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+  for (i, a_char) in a.chars().enumerate() {
+    let mut current = vec![i + 1];
+    for (j, b_char) in b.iter().enumerate() {
+      let cost = usize::from(a_char != *b_char);
+      current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+    }
+    previous = current;
+  }
+
+  previous[b.len()]
+}
+
+// Choose existing versions nearest to required non exists version: latest stable version
+// with same numbers except last (same major/minor with latest patch) and versions
+// in small edit distance, newer versions first
+pub(crate) fn suggest_versions(version: &str, available: &[String]) -> Vec<String> {
+  let numbers = get_version_numbers(version);
+  let mut suggestions: Vec<String> = Vec::new();
+
+  if numbers.len() > 1 {
+    let prefix = &numbers[..numbers.len() - 1];
+
+    let latest = available
+      .iter()
+      .filter(|candidate| is_stable_version(candidate))
+      .filter(|candidate| get_version_numbers(candidate).starts_with(prefix))
+      .max_by_key(|candidate| get_version_numbers(candidate));
+
+    if let Some(latest) = latest {
+      suggestions.push(latest.clone());
+    }
+  }
+
+  let mut similar: Vec<(usize, Vec<u64>, &String)> = available
+    .iter()
+    .map(|candidate| (edit_distance(version, candidate), candidate))
+    .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+    .map(|(distance, candidate)| (distance, get_version_numbers(candidate), candidate))
+    .collect();
+  similar.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)));
+
+  for (_, _, candidate) in similar {
+    if !suggestions.contains(candidate) {
+      suggestions.push(candidate.clone());
+    }
+  }
+
+  suggestions.truncate(MAX_SUGGESTIONS);
+  suggestions
+}
+
+// Operating systems parts of protoc assets platforms, windows assets platforms
+// don't have a separator between os and arch
+static PLATFORM_OS_PREFIXES: [(&str, &str); 3] =
+  [("linux-", "linux"), ("osx-", "macos"), ("win", "windows")];
+
+// Check is platform in protoc assets names format, for example, "linux-aarch_64",
+// "osx-universal_binary" or "win64"
+pub(crate) fn validate_platform<'a>(platform: &str) -> Result<(), Error<'a>> {
+  let is_valid = PLATFORM_OS_PREFIXES.iter().any(|(prefix, _)| {
+    platform.strip_prefix(prefix).is_some_and(|arch| {
+      !arch.is_empty() &&
+      arch.chars().all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '-')
+    })
+  });
+
+  match is_valid {
+    true => Ok(()),
+    false => Err(Error::InvalidPlatform(platform.to_string()))
+  }
+}
+
+// Get operating system name (in `std::env::consts::OS` format) of protoc asset platform
+pub(crate) fn get_platform_os(platform: &str) -> &'static str {
+  PLATFORM_OS_PREFIXES
+    .iter()
+    .find(|(prefix, _)| platform.starts_with(prefix))
+    .map_or("", |(_, os)| os)
+}
+
+// Format protoc pre-built package name for explicitly defined platform
+pub(crate) fn get_protoc_platform_asset_name(version: &str, platform: &str) -> String {
+  format!("protoc-{}-{}", prepare_asset_version(version), platform)
+}
+
+// Format protoc pre-built packages names in preference order: first is name for target
+// platform, next are fallbacks
+//
+// For macOS fallbacks are universal binary (provided from "21.0" version) and, for Apple
+// Silicon, x86_64 binary, which can be run by Rosetta (for versions before "3.20.0",
+// which don't have aarch64 binaries)
+pub(crate) fn get_protoc_asset_names<'a>(
+  version: &str, os: &str, arch: &str
+) -> Result<Vec<String>, Error<'a>> {
+  let mut names = vec![get_protoc_asset_name(version, os, arch)?];
+
+  if os == "macos" && (arch == "aarch64" || arch == "x86_64") {
+    names.push(format!("protoc-{}-osx-universal_binary", prepare_asset_version(version)));
+  }
+  if os == "macos" && arch == "aarch64" {
+    names.push(get_protoc_asset_name(version, os, "x86_64")?);
+  }
+
+  Ok(names)
+}
+
+// Convert machine name returned by `uname -m` or Windows `PROCESSOR_ARCHITECTURE` environment
+// variable to `std::env::consts::ARCH` format
+fn normalize_arch(machine: &str) -> Option<&'static str> {
+  match machine.trim() {
+    "x86_64" | "amd64" | "AMD64" => Some("x86_64"),
+    "aarch64" | "arm64" | "ARM64" => Some("aarch64"),
+    "i386" | "i486" | "i586" | "i686" | "x86" => Some("x86"),
+    "s390x" => Some("s390x"),
+    "ppc64le" => Some("powerpc64"),
+    _ => None
+  }
+}
+
+// Inner testable logic of running host architecture detection, commands runner and
+// environment variables getter are passed to replace them in tests, return compile-time
+// architecture if detection fail
+//
+// - on macOS `hw.optional.arm64` sysctl is "1" on Apple Silicon, even if process run
+//   by Rosetta (`uname -m` return "x86_64" in this case);
+// - on Windows `PROCESSOR_ARCHITEW6432` environment variable defined for 32-bit process
+//   on 64-bit system;
+// - on other systems `uname -m` used.
+pub(crate) fn detect_host_arch<'a, R, V>(os: &str, arch: &'a str, run: R, get_var: V) -> &'a str
+where
+  R: Fn(&str, &[&str]) -> Option<String>,
+  V: Fn(&str) -> Option<String>
+{
+  let detected = match os {
+    "windows" => get_var("PROCESSOR_ARCHITEW6432")
+      .or_else(|| get_var("PROCESSOR_ARCHITECTURE"))
+      .and_then(|machine| normalize_arch(&machine)),
+    "macos" => match run("sysctl", &["-n", "hw.optional.arm64"]).as_deref().map(str::trim) {
+      Some("1") => Some("aarch64"),
+      _ => run("uname", &["-m"]).and_then(|machine| normalize_arch(&machine))
+    },
+    _ => run("uname", &["-m"]).and_then(|machine| normalize_arch(&machine))
+  };
+
+  detected.unwrap_or(arch)
+}
+
+// Run command and return it stdout, if it success
+fn run_command(program: &str, args: &[&str]) -> Option<String> {
+  let output = Command::new(program).args(args).output().ok()?;
+  match output.status.success() {
+    true => String::from_utf8(output.stdout).ok(),
+    false => None
+  }
+}
+
+// Get running host architecture, detection can be disabled
+// by `PROTOC_PREBUILT_NOT_DETECT_HOST` environment variable
+pub(crate) fn get_host_arch<'a>(os: &str, arch: &'a str) -> &'a str {
+  if var_bool("PROTOC_PREBUILT_NOT_DETECT_HOST") {
+    return arch
+  }
+
+  detect_host_arch(os, arch, run_command, |key| var(key).ok())
+}
+
+// Format protoc pre-built packages names for running host architecture in preference order,
+// if it differs from compile-time architecture, names for compile-time architecture
+// are used as fallbacks (for example, for Windows on ARM, which don't have native binaries,
+// but can run x86_64 binaries by emulation)
+pub(crate) fn get_protoc_host_asset_names<'a>(
+  version: &str, os: &str, arch: &str, host_arch: &str
+) -> Result<Vec<String>, Error<'a>> {
+  let target_names = get_protoc_asset_names(version, os, arch);
+  if host_arch == arch {
+    return target_names
+  }
+
+  let mut names = match get_protoc_asset_names(version, os, host_arch) {
+    Ok(names) => names,
+    Err(_) => return target_names
+  };
+
+  for name in target_names.unwrap_or_default() {
+    if !names.contains(&name) {
+      names.push(name);
+    }
+  }
+
+  Ok(names)
+}
+
 #[cfg(test)]
 mod test {
   use crate::error::Error;
-  use super::{ compare_versions, get_protoc_asset_name, prepare_asset_version };
+  use super::{
+    compare_versions,
+    detect_host_arch,
+    edit_distance,
+    get_protoc_asset_name,
+    get_protoc_asset_names,
+    get_protoc_host_asset_names,
+    get_platform_os,
+    get_protoc_platform_asset_name,
+    get_version_numbers,
+    prepare_asset_version,
+    suggest_versions,
+    validate_platform,
+    validate_version
+  };
 
   fn check_protoc_assets_name_ok(result: Result<String, Error>, expect: &str) {
     assert!(result.is_ok());
@@ -288,4 +570,165 @@ mod test {
     check_get_protoc_asset_name_err(get_protoc_asset_name("22.0", "freebsd", "aarch64"));
     check_get_protoc_asset_name_err(get_protoc_asset_name("22.0", "windows", "aarch64"));
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn version_numbers() {
+    assert_eq!(get_version_numbers("3.21.12"), vec![3, 21, 12]);
+    assert_eq!(get_version_numbers("22.0-rc3"), vec![22, 0]);
+    assert_eq!(get_version_numbers("3.7.0rc2"), vec![3, 7, 0]);
+    assert_eq!(get_version_numbers("3.0.0-beta-4"), vec![3, 0, 0]);
+    assert_eq!(get_version_numbers("3.7.0-rc.3"), vec![3, 7, 0]);
+  }
+
+  #[test]
+  fn edit_distances() {
+    assert_eq!(edit_distance("22.1", "22.1"), 0);
+    assert_eq!(edit_distance("22.1", "22.2"), 1);
+    assert_eq!(edit_distance("3.21.1", "3.21.12"), 1);
+    assert_eq!(edit_distance("", "abc"), 3);
+  }
+
+  #[test]
+  fn suggest_nearest_versions() {
+    let available: Vec<String> = [
+      "3.20.3", "3.21.0-rc1", "3.21.0", "3.21.1", "3.21.12", "3.21.2", "21.12", "22.0-rc3",
+      "22.0", "22.5", "23.0"
+    ].iter().map(|version| version.to_string()).collect();
+
+    assert_eq!(
+      suggest_versions("3.21.13", &available),
+      vec!["3.21.12", "3.21.1", "3.21.2", "3.21.0", "3.20.3"]
+    );
+    assert_eq!(suggest_versions("22.6", &available), vec!["22.5", "22.0", "23.0"]);
+    assert_eq!(
+      suggest_versions("21.0", &available), vec!["21.12", "23.0", "22.0", "22.5", "3.21.0"]
+    );
+    assert!(suggest_versions("100.0.0.0", &available).is_empty());
+  }
+
+  #[test]
+  fn valid_versions() {
+    for version in [
+      "2.4.1", "22.0", "3.21.12", "22.0-rc3", "26.0-rc1", "3.7.0-rc.3", "3.7.0rc2", "3.2.0rc2",
+      "3.0.0-alpha-1", "3.0.0-beta-4", "3.14.0-rc2", "3.0.0.1"
+    ] {
+      assert!(validate_version(version).is_ok(), "{}", version);
+    }
+  }
+
+  #[test]
+  fn invalid_versions() {
+    for version in [
+      "", "22", "v22.0", " 22.0", "22.0 ", "22.0/../..", "../22.0", "22..0", "22.0.", ".22.0",
+      "22.0-rc3/x", "22.0-gamma", "22.0-rc-x", "22.0-rc3-rc4", "1.2.3.4.5", "22.0\\a", "22.0?a"
+    ] {
+      assert!(
+        matches!(validate_version(version).unwrap_err(), Error::InvalidVersion { .. }),
+        "{}", version
+      );
+    }
+  }
+
+  #[test]
+  fn get_protoc_assets_names_fallbacks() {
+    assert_eq!(
+      get_protoc_asset_names("3.19.4", "macos", "aarch64").unwrap(),
+      vec![
+        "protoc-3.19.4-osx-aarch_64",
+        "protoc-3.19.4-osx-universal_binary",
+        "protoc-3.19.4-osx-x86_64"
+      ]
+    );
+    assert_eq!(
+      get_protoc_asset_names("22.0-rc3", "macos", "x86_64").unwrap(),
+      vec!["protoc-22.0-rc-3-osx-x86_64", "protoc-22.0-rc-3-osx-universal_binary"]
+    );
+    assert_eq!(get_protoc_asset_names("22.0", "linux", "x86_64").unwrap(), vec![
+      "protoc-22.0-linux-x86_64"
+    ]);
+    assert!(get_protoc_asset_names("22.0", "freebsd", "x86_64").is_err());
+  }
+
+  #[test]
+  fn platforms() {
+    for platform in ["linux-aarch_64", "linux-x86-32", "osx-universal_binary", "win64", "win32"] {
+      assert!(validate_platform(platform).is_ok(), "{}", platform);
+    }
+    for platform in ["", "linux", "linux-", "freebsd-x86_64", "win", "linux-../x", "win 64"] {
+      assert!(matches!(validate_platform(platform).unwrap_err(), Error::InvalidPlatform { .. }));
+    }
+
+    assert_eq!(get_platform_os("linux-aarch_64"), "linux");
+    assert_eq!(get_platform_os("osx-x86_64"), "macos");
+    assert_eq!(get_platform_os("win64"), "windows");
+
+    assert_eq!(
+      get_protoc_platform_asset_name("22.0-rc3", "linux-aarch_64"),
+      "protoc-22.0-rc-3-linux-aarch_64"
+    );
+  }
+
+  #[test]
+  fn host_arch_detection() {
+    let no_var = |_: &str| None;
+
+    // Linux x86_64 toolchain run on aarch64 host
+    let run = |program: &str, _: &[&str]| match program {
+      "uname" => Some(String::from("aarch64\n")),
+      _ => None
+    };
+    assert_eq!(detect_host_arch("linux", "x86_64", run, no_var), "aarch64");
+
+    // macOS x86_64 toolchain run by Rosetta
+    let run = |program: &str, _: &[&str]| match program {
+      "sysctl" => Some(String::from("1\n")),
+      _ => Some(String::from("x86_64\n"))
+    };
+    assert_eq!(detect_host_arch("macos", "x86_64", run, no_var), "aarch64");
+
+    // macOS on Intel
+    let run = |program: &str, _: &[&str]| match program {
+      "sysctl" => None,
+      _ => Some(String::from("x86_64\n"))
+    };
+    assert_eq!(detect_host_arch("macos", "x86_64", run, no_var), "x86_64");
+
+    // 32-bit Windows toolchain on 64-bit system
+    let get_var = |key: &str| match key {
+      "PROCESSOR_ARCHITEW6432" => Some(String::from("AMD64")),
+      _ => Some(String::from("x86"))
+    };
+    assert_eq!(detect_host_arch("windows", "x86", |_: &str, _: &[&str]| None, get_var), "x86_64");
+
+    // Detection fail
+    let run = |_: &str, _: &[&str]| Some(String::from("unknown"));
+    assert_eq!(detect_host_arch("linux", "x86_64", run, no_var), "x86_64");
+    assert_eq!(detect_host_arch("linux", "x86_64", |_: &str, _: &[&str]| None, no_var), "x86_64");
+  }
+
+  #[test]
+  fn get_protoc_host_assets_names() {
+    assert_eq!(
+      get_protoc_host_asset_names("22.0", "linux", "x86_64", "x86_64").unwrap(),
+      vec!["protoc-22.0-linux-x86_64"]
+    );
+    assert_eq!(
+      get_protoc_host_asset_names("22.0", "linux", "x86_64", "aarch64").unwrap(),
+      vec!["protoc-22.0-linux-aarch_64", "protoc-22.0-linux-x86_64"]
+    );
+    assert_eq!(
+      get_protoc_host_asset_names("3.19.4", "macos", "x86_64", "aarch64").unwrap(),
+      vec![
+        "protoc-3.19.4-osx-aarch_64",
+        "protoc-3.19.4-osx-universal_binary",
+        "protoc-3.19.4-osx-x86_64"
+      ]
+    );
+    // Windows on ARM don't have native binaries
+    assert_eq!(
+      get_protoc_host_asset_names("22.0", "windows", "x86_64", "aarch64").unwrap(),
+      vec!["protoc-22.0-win64"]
+    );
+    assert!(get_protoc_host_asset_names("22.0", "freebsd", "x86_64", "aarch64").is_err());
+  }
+}
@@ -1,4 +1,4 @@
-use protoc_prebuilt::{ Error, init };
+use protoc_prebuilt::{ Error, init, init_installation };
 use std::{
   env::{ set_var, temp_dir },
   fs::{ create_dir_all, metadata, remove_dir_all, remove_file },
@@ -36,7 +36,16 @@ fn test_integration() {
 
   // Check installation paths exists
   assert!(metadata(&protoc_bin).is_ok());
-  assert!(metadata(protoc_include).is_ok());
+  assert!(metadata(&protoc_include).is_ok());
+
+  // Check installation information of already installed binary
+  let result = init_installation(version);
+  assert!(result.is_ok());
+  let installation = result.unwrap();
+  assert_eq!(installation.bin, protoc_bin);
+  assert_eq!(installation.include, protoc_include);
+  assert!(installation.asset.is_some_and(|asset| asset.starts_with("protoc-22.0-")));
+  assert!(installation.platform.is_some());
 
   // Delete protoc binary to check what in next initialization not run installation
   remove_file(&protoc_bin).unwrap();